@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sqlite3_header::{peek, SQLite3Header, SQLite3HeaderRef};
+
+const SMALL_DB: &[u8] = include_bytes!("../tests/fixtures/small.db");
+
+/// Reading a few fields through the borrowed view, against parsing every field
+/// into an owned header and reading the same ones.
+fn borrowed_view(c: &mut Criterion) {
+    let mut group = c.benchmark_group("borrowed_view");
+    group.bench_function("SQLite3HeaderRef::new", |b| {
+        b.iter(|| {
+            let header = SQLite3HeaderRef::new(black_box(SMALL_DB)).unwrap();
+            (header.page_size_bytes(), header.freelist_count(), header.user_version())
+        })
+    });
+    group.bench_function("SQLite3Header::from_bytes", |b| {
+        b.iter(|| {
+            let header = SQLite3Header::from_bytes(black_box(SMALL_DB)).unwrap();
+            (header.page_size_bytes(), header.freelist().count, header.user_version())
+        })
+    });
+    group.finish();
+}
+
+/// Reading the page size and text encoding with `peek`, against parsing the whole
+/// header for them.
+fn peek_fields(c: &mut Criterion) {
+    let mut group = c.benchmark_group("peek");
+    group.bench_function("peek", |b| b.iter(|| peek(black_box(SMALL_DB)).unwrap()));
+    group.bench_function("SQLite3Header::from_bytes", |b| {
+        b.iter(|| {
+            let header = SQLite3Header::from_bytes(black_box(SMALL_DB)).unwrap();
+            (header.page_size(), *header.database_text_encoding())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, borrowed_view, peek_fields);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+use sqlite3_header::SQLite3Header;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "data.sqlite".to_owned());
+    let header = SQLite3Header::from_file(path)?;
+    println!("{}", serde_json::to_string_pretty(&header)?);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use core::convert::TryInto;
+
+use crate::{
+    check_length_and_magic, error::Error, file_format_from_byte, i32_at, offsets,
+    page_size_to_bytes, text_encoding_from_u32, u16_at, u32_at, DatabaseTextEncoding,
+    FileFormat, SQLite3Header, HEADER_SIZE,
+};
+
+/// A borrowed view of a database header that decodes each field on demand rather
+/// than copying every field up front. Only the length and magic header string are
+/// checked when the view is created; use [`SQLite3HeaderRef::to_owned`] to run the
+/// full validation.
+#[derive(Debug, Clone, Copy)]
+pub struct SQLite3HeaderRef<'a>(&'a [u8; HEADER_SIZE]);
+
+impl<'a> SQLite3HeaderRef<'a> {
+    /// Borrows the first 100 bytes of `bytes` as a header.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        check_length_and_magic(bytes)?;
+        let header = bytes[..HEADER_SIZE].try_into().map_err(|_| Error::TooShort {
+            expected: HEADER_SIZE,
+            found: bytes.len(),
+        })?;
+        Ok(Self(header))
+    }
+
+    /// Borrows the first 100 bytes of a [`bytes::Bytes`] buffer as a header.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &'a bytes::Bytes) -> Result<Self, Error> {
+        Self::new(buf)
+    }
+
+    /// The borrowed 100 header bytes.
+    pub fn as_bytes(&self) -> &'a [u8; HEADER_SIZE] {
+        self.0
+    }
+
+    /// Parses and validates every field into an owned [`SQLite3Header`].
+    pub fn to_owned(&self) -> Result<SQLite3Header, Error> {
+        SQLite3Header::from_bytes(self.0)
+    }
+
+    fn u32_at(&self, offset: usize) -> u32 {
+        u32_at(self.0, offset).unwrap_or_default()
+    }
+
+    /// The raw on-disk page size field.
+    pub fn page_size(&self) -> u16 {
+        u16_at(self.0, offsets::PAGE_SIZE).unwrap_or_default()
+    }
+
+    /// The page size in bytes, resolving the on-disk value 1 to 65536.
+    pub fn page_size_bytes(&self) -> u32 {
+        page_size_to_bytes(self.page_size())
+    }
+
+    pub fn file_format_write_version(&self) -> Result<FileFormat, Error> {
+        file_format_from_byte(
+            offsets::FILE_FORMAT_WRITE_VERSION,
+            self.0[offsets::FILE_FORMAT_WRITE_VERSION],
+        )
+    }
+
+    pub fn file_format_read_version(&self) -> Result<FileFormat, Error> {
+        file_format_from_byte(
+            offsets::FILE_FORMAT_READ_VERSION,
+            self.0[offsets::FILE_FORMAT_READ_VERSION],
+        )
+    }
+
+    pub fn reserved_bytes_per_page(&self) -> u8 {
+        self.0[offsets::RESERVED_BYTES_PER_PAGE]
+    }
+
+    pub fn file_change_counter(&self) -> u32 {
+        self.u32_at(offsets::FILE_CHANGE_COUNTER)
+    }
+
+    pub fn in_header_database_size(&self) -> u32 {
+        self.u32_at(offsets::IN_HEADER_DATABASE_SIZE)
+    }
+
+    pub fn freelist_page_index(&self) -> u32 {
+        self.u32_at(offsets::FREELIST_PAGE_INDEX)
+    }
+
+    pub fn freelist_count(&self) -> u32 {
+        self.u32_at(offsets::FREELIST_COUNT)
+    }
+
+    pub fn schema_cookie(&self) -> u32 {
+        self.u32_at(offsets::SCHEMA_COOKIE)
+    }
+
+    pub fn default_page_cache_size(&self) -> i32 {
+        i32_at(self.0, offsets::DEFAULT_PAGE_CACHE_SIZE).unwrap_or_default()
+    }
+
+    pub fn largest_root_btree_page(&self) -> u32 {
+        self.u32_at(offsets::LARGEST_ROOT_BTREE_PAGE)
+    }
+
+    pub fn database_text_encoding(&self) -> Result<DatabaseTextEncoding, Error> {
+        text_encoding_from_u32(self.u32_at(offsets::DATABASE_TEXT_ENCODING))
+    }
+
+    pub fn user_version(&self) -> u32 {
+        self.u32_at(offsets::USER_VERSION)
+    }
+
+    pub fn application_id(&self) -> u32 {
+        self.u32_at(offsets::APPLICATION_ID)
+    }
+
+    /// The reserved expansion bytes at offsets 72 through 91, which should be zero.
+    pub fn reserved_expansion(&self) -> &'a [u8; 20] {
+        let reserved = &self.0[offsets::RESERVED..offsets::RESERVED + offsets::RESERVED_LEN];
+        reserved.try_into().unwrap_or(&[0; 20])
+    }
+
+    pub fn version_valid_for(&self) -> u32 {
+        self.u32_at(offsets::VERSION_VALID_FOR)
+    }
+
+    pub fn sqlite_version_number(&self) -> u32 {
+        self.u32_at(offsets::SQLITE_VERSION_NUMBER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_DB: &[u8] = include_bytes!("../tests/fixtures/small.db");
+    const UTF16_DB: &[u8] = include_bytes!("../tests/fixtures/utf16.db");
+    const AUTOVACUUM_DB: &[u8] = include_bytes!("../tests/fixtures/autovacuum.db");
+
+    #[test]
+    fn matches_owned_parser() {
+        for &db in &[SMALL_DB, UTF16_DB, AUTOVACUUM_DB] {
+            let view = SQLite3HeaderRef::new(db).unwrap();
+            let owned = SQLite3Header::from_bytes(db).unwrap();
+            assert_eq!(view.to_owned().as_ref(), Ok(&owned));
+            assert_eq!(&view.as_bytes()[..], &db[..HEADER_SIZE]);
+
+            assert_eq!(view.page_size_bytes(), owned.page_size_bytes());
+            assert_eq!(view.page_size(), owned.page_size().on_disk());
+            assert_eq!(
+                view.file_format_write_version().as_ref(),
+                Ok(owned.file_format_write_version())
+            );
+            assert_eq!(
+                view.file_format_read_version().as_ref(),
+                Ok(owned.file_format_read_version())
+            );
+            assert_eq!(view.reserved_bytes_per_page(), owned.reserved_bytes_per_page());
+            assert_eq!(view.file_change_counter(), owned.file_change_counter());
+            assert_eq!(view.in_header_database_size(), owned.in_header_database_size());
+            assert_eq!(view.freelist_page_index(), owned.freelist().page_index);
+            assert_eq!(view.freelist_count(), owned.freelist().count);
+            assert_eq!(view.schema_cookie(), owned.schema().cookie);
+            assert_eq!(view.default_page_cache_size(), owned.default_page_cache_size());
+            assert_eq!(
+                view.largest_root_btree_page(),
+                owned.vacuum().map_or(0, |vacuum| vacuum.largest_root_btree_page),
+            );
+            assert_eq!(view.database_text_encoding().as_ref(), Ok(owned.database_text_encoding()));
+            assert_eq!(view.user_version(), owned.user_version());
+            assert_eq!(view.application_id(), owned.application_id());
+            assert_eq!(view.reserved_expansion(), owned.reserved_expansion());
+            assert_eq!(view.version_valid_for(), owned.last_update().version_valid_for);
+            assert_eq!(view.sqlite_version_number(), owned.last_update().sqlite_version_number);
+        }
+    }
+
+    #[test]
+    fn only_checks_length_and_magic() {
+        let mut bytes = SQLite3Header::default().to_bytes();
+        bytes[offsets::DATABASE_TEXT_ENCODING + 3] = 7;
+        let view = SQLite3HeaderRef::new(&bytes).unwrap();
+        assert_eq!(view.database_text_encoding(), Err(Error::InvalidTextEncoding(7)));
+        assert_eq!(view.to_owned(), Err(Error::InvalidTextEncoding(7)));
+
+        assert_eq!(
+            SQLite3HeaderRef::new(&bytes[..99]).unwrap_err(),
+            Error::TooShort { expected: 100, found: 99 },
+        );
+        bytes[0] = 0;
+        assert!(matches!(SQLite3HeaderRef::new(&bytes), Err(Error::InvalidMagicHeaderString(_))));
+    }
+}
@@ -0,0 +1,450 @@
+// https://sqlite.org/fileformat2.html#b_tree_pages
+
+use crate::{error::Error, four_byte_slice_to_u32, page, two_byte_slice_to_u16, varint::read_varint};
+
+/// The one-byte flag at offset 0 indicating the b-tree page type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageType {
+    /// A value of 2 (0x02) means the page is an interior index b-tree page.
+    InteriorIndex,
+    /// A value of 5 (0x05) means the page is an interior table b-tree page.
+    InteriorTable,
+    /// A value of 10 (0x0a) means the page is a leaf index b-tree page.
+    LeafIndex,
+    /// A value of 13 (0x0d) means the page is a leaf table b-tree page.
+    LeafTable,
+}
+
+impl PageType {
+    pub fn from_byte(value: u8) -> Result<Self, Error> {
+        match value {
+            0x02 => Ok(Self::InteriorIndex),
+            0x05 => Ok(Self::InteriorTable),
+            0x0a => Ok(Self::LeafIndex),
+            0x0d => Ok(Self::LeafTable),
+            _ => Err(Error::InvalidPageType(value)),
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        matches!(self, Self::LeafIndex | Self::LeafTable)
+    }
+}
+
+/// The b-tree page header is 8 bytes in size for leaf pages and 12 bytes for
+/// interior pages. All multibyte values in the page header are big-endian. The
+/// b-tree page header of page 1 follows the 100-byte database file header.
+#[derive(Debug)]
+pub struct BTreePageHeader {
+    pub page_type: PageType,
+    /// The start of the first freeblock on the page, or zero if there are no
+    /// freeblocks.
+    pub first_freeblock: u16,
+    /// The number of cells on the page.
+    pub cell_count: u16,
+    /// The start of the cell content area. A zero value for this integer is
+    /// interpreted as 65536.
+    pub cell_content_start: u32,
+    /// The number of fragmented free bytes within the cell content area.
+    pub fragmented_free_bytes: u8,
+    /// The right-most pointer. This value appears in the header of interior b-tree
+    /// pages only and is omitted from all other pages.
+    pub right_most_pointer: Option<u32>,
+}
+
+impl BTreePageHeader {
+    /// Parses the b-tree page header at the start of `bytes`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 8 {
+            return Err(Error::TooShort {
+                expected: 8,
+                found: bytes.len(),
+            });
+        }
+
+        let page_type = PageType::from_byte(bytes[0])?;
+        let right_most_pointer = if page_type.is_leaf() {
+            None
+        } else if bytes.len() < 12 {
+            return Err(Error::TooShort {
+                expected: 12,
+                found: bytes.len(),
+            });
+        } else {
+            Some(four_byte_slice_to_u32(&bytes[8..12])?)
+        };
+
+        Ok(Self {
+            page_type,
+            first_freeblock: two_byte_slice_to_u16(&bytes[1..3])?,
+            cell_count: two_byte_slice_to_u16(&bytes[3..5])?,
+            cell_content_start: match two_byte_slice_to_u16(&bytes[5..7])? {
+                0 => 65536,
+                v => u32::from(v),
+            },
+            fragmented_free_bytes: bytes[7],
+            right_most_pointer,
+        })
+    }
+
+    /// The size of the page header in bytes: 12 for interior pages and 8 for leaves.
+    pub fn size(&self) -> usize {
+        if self.page_type.is_leaf() {
+            8
+        } else {
+            12
+        }
+    }
+
+    /// Iterates over the cell pointer array that immediately follows the page header.
+    /// Each pointer is the offset of a cell from the start of `page`, which should
+    /// hold the usable region of the whole page. `header_offset` is where the page
+    /// header begins: 100 on page 1, after the database header, and 0 otherwise. A
+    /// pointer that falls before the end of the array or too close to the end of the
+    /// page to hold a cell is an error.
+    pub fn cell_pointers<'a>(&self, page: &'a [u8], header_offset: usize) -> CellPointers<'a> {
+        let start = header_offset + self.size();
+        CellPointers {
+            page,
+            at: start,
+            end: start + 2 * usize::from(self.cell_count),
+        }
+    }
+}
+
+/// An iterator over the cell pointer array of a b-tree page, created by
+/// [`BTreePageHeader::cell_pointers`].
+#[derive(Debug, Clone)]
+pub struct CellPointers<'a> {
+    page: &'a [u8],
+    /// The offset of the next pointer to read.
+    at: usize,
+    /// The offset of the end of the cell pointer array.
+    end: usize,
+}
+
+impl Iterator for CellPointers<'_> {
+    type Item = Result<u16, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.at >= self.end {
+            return None;
+        }
+        let at = self.at;
+        self.at += 2;
+
+        let pointer = match self.page.get(at..at + 2) {
+            Some(pointer) => pointer,
+            None => {
+                return Some(Err(Error::TooShort {
+                    expected: at + 2,
+                    found: self.page.len(),
+                }))
+            }
+        };
+        Some(two_byte_slice_to_u16(pointer).and_then(|pointer| {
+            let offset = usize::from(pointer);
+            if offset < self.end || offset > self.page.len().saturating_sub(4) {
+                return Err(Error::InvalidCellPointer(pointer));
+            }
+            Ok(pointer)
+        }))
+    }
+}
+
+/// The largest payload that is stored entirely on a page of type `page_type`: X in
+/// the spec. This is U-35 for table b-tree leaf pages and ((U-12)*64/255)-23 for
+/// index pages, where U is the usable size. Interior table pages hold no payloads.
+/// The usable size is never less than 480, but smaller values saturate at zero
+/// rather than overflowing.
+pub(crate) fn max_local_payload(page_type: &PageType, usable_size: u32) -> u32 {
+    match page_type {
+        PageType::LeafTable => usable_size.saturating_sub(35),
+        _ => (usable_size.saturating_sub(12) * 64 / 255).saturating_sub(23),
+    }
+}
+
+/// The number of payload bytes that are stored on the page when a payload
+/// overflows, at minimum: M in the spec, ((U-12)*32/255)-23 for every page type.
+pub(crate) fn min_local_payload(usable_size: u32) -> u32 {
+    (usable_size.saturating_sub(12) * 32 / 255).saturating_sub(23)
+}
+
+/// A cell of a table b-tree leaf page, holding one row of the table.
+#[derive(Debug)]
+pub struct TableLeafCell<'a> {
+    /// The total number of bytes of payload, including any overflow.
+    pub payload_size: u64,
+    /// The integer key, a.k.a. "rowid".
+    pub rowid: i64,
+    /// The initial portion of the payload that is stored on the b-tree page.
+    pub local_payload: &'a [u8],
+    /// The page number of the first overflow page, if the payload does not fit on the
+    /// b-tree page.
+    pub overflow_page: Option<u32>,
+}
+
+/// Parses the table b-tree leaf cell at the start of `bytes`, given the usable size
+/// of the page and X, the largest payload stored entirely on the page, as returned
+/// by `SQLite3Header::max_local_payload`. When the payload is larger than X, only
+/// part of it is stored on the page and the cell ends with the number of the first
+/// overflow page.
+pub fn parse_table_leaf_cell(
+    bytes: &[u8],
+    usable_size: u32,
+    max_local: u32,
+) -> Result<TableLeafCell<'_>, Error> {
+    let (payload_size, n) = read_varint(bytes)?;
+    let (rowid, m) = read_varint(&bytes[n..])?;
+    let rest = &bytes[n + m..];
+
+    let local = local_payload(payload_size, usable_size, max_local);
+    let too_short = |expected| Error::TooShort {
+        expected: n + m + expected,
+        found: bytes.len(),
+    };
+    let local_payload = rest.get(..local).ok_or_else(|| too_short(local))?;
+    let overflow_page = if (local as u64) < payload_size {
+        let pointer = rest.get(local..local + 4).ok_or_else(|| too_short(local + 4))?;
+        Some(four_byte_slice_to_u32(pointer)?)
+    } else {
+        None
+    };
+
+    Ok(TableLeafCell {
+        payload_size,
+        rowid: rowid as i64,
+        local_payload,
+        overflow_page,
+    })
+}
+
+/// The number of bytes of a payload of `payload_size` bytes that are stored on the
+/// b-tree page, given the page's usable size and X, the largest payload stored
+/// entirely on the page.
+fn local_payload(payload_size: u64, usable_size: u32, max_local: u32) -> usize {
+    let max_local = u64::from(max_local);
+    if payload_size <= max_local {
+        return payload_size as usize;
+    }
+
+    let min_local = u64::from(min_local_payload(usable_size));
+    let overflow_content = u64::from(usable_size).saturating_sub(4).max(1);
+    let local = min_local + payload_size.saturating_sub(min_local) % overflow_content;
+    if local <= max_local {
+        local as usize
+    } else {
+        min_local as usize
+    }
+}
+
+/// When the size of payload for a cell exceeds a certain threshold, then only the
+/// first few bytes of the payload are stored on the b-tree page and the balance is
+/// stored in a linked list of content overflow pages. The first four bytes of each
+/// overflow page are a big-endian integer which is the page number of the next page
+/// in the chain, or zero for the final page in the chain. The fifth byte through the
+/// last usable byte are used to hold overflow content.
+///
+/// Each item yielded is the content of one overflow page. A chain is never followed
+/// for more pages than the database holds, which guards against cycles.
+#[derive(Debug)]
+pub struct OverflowChain<'a> {
+    db: &'a [u8],
+    page_size: u32,
+    usable_size: u32,
+    next_page: u32,
+    remaining: usize,
+}
+
+impl<'a> OverflowChain<'a> {
+    pub fn new(first_page: u32, db: &'a [u8], page_size: u32, usable_size: u32) -> Self {
+        Self {
+            db,
+            page_size,
+            usable_size,
+            next_page: first_page,
+            remaining: db.len() / page_size.max(1) as usize,
+        }
+    }
+
+    fn next_content(&mut self) -> Result<&'a [u8], Error> {
+        if self.remaining == 0 {
+            return Err(Error::PageCycle(self.next_page));
+        }
+        self.remaining -= 1;
+
+        let overflow = page(self.db, self.next_page, self.page_size)?;
+        let usable_size = (self.usable_size as usize).min(overflow.len());
+        if usable_size < 4 {
+            return Err(Error::PageOutOfBounds(self.next_page));
+        }
+        self.next_page = four_byte_slice_to_u32(&overflow[0..4])?;
+        Ok(&overflow[4..usable_size])
+    }
+}
+
+impl<'a> Iterator for OverflowChain<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_page == 0 {
+            return None;
+        }
+
+        let content = self.next_content();
+        if content.is_err() {
+            self.next_page = 0;
+        }
+        Some(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{vec, vec::Vec};
+
+    use super::*;
+
+    /// A database whose schema b-tree root, page 1, is an interior page.
+    const SMALL_DB: &[u8] = include_bytes!("../tests/fixtures/small.db");
+
+    #[test]
+    fn page_types() {
+        assert_eq!(PageType::from_byte(0x02), Ok(PageType::InteriorIndex));
+        assert_eq!(PageType::from_byte(0x0d), Ok(PageType::LeafTable));
+        assert_eq!(PageType::from_byte(0x01), Err(Error::InvalidPageType(0x01)));
+        assert!(PageType::LeafIndex.is_leaf());
+        assert!(!PageType::InteriorTable.is_leaf());
+    }
+
+    #[test]
+    fn parses_interior_page_header() {
+        let header = BTreePageHeader::parse(&SMALL_DB[100..512]).unwrap();
+        assert_eq!(header.page_type, PageType::InteriorTable);
+        assert_eq!(header.cell_count, 1);
+        assert_eq!(header.right_most_pointer, Some(7));
+        assert_eq!(header.size(), 12);
+
+        let pointers = header.cell_pointers(&SMALL_DB[..512], 100).collect::<Vec<_>>();
+        assert_eq!(pointers, vec![Ok(header.cell_content_start as u16)]);
+    }
+
+    #[test]
+    fn parses_leaf_cells() {
+        let page = &SMALL_DB[512..1024];
+        let header = BTreePageHeader::parse(page).unwrap();
+        assert_eq!(header.page_type, PageType::LeafTable);
+        assert_eq!(header.right_most_pointer, None);
+        assert_eq!(header.size(), 8);
+
+        let rowids = header
+            .cell_pointers(page, 0)
+            .map(|pointer| {
+                let cell = &page[usize::from(pointer.unwrap())..];
+                let max_local = max_local_payload(&PageType::LeafTable, 512);
+                let cell = parse_table_leaf_cell(cell, 512, max_local).unwrap();
+                assert_eq!(cell.overflow_page, None);
+                assert_eq!(cell.local_payload.len() as u64, cell.payload_size);
+                cell.rowid
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rowids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_short_headers() {
+        assert_eq!(
+            BTreePageHeader::parse(&[0x0d; 7]).unwrap_err(),
+            Error::TooShort { expected: 8, found: 7 },
+        );
+        assert_eq!(
+            BTreePageHeader::parse(&[0x05; 11]).unwrap_err(),
+            Error::TooShort { expected: 12, found: 11 },
+        );
+    }
+
+    #[test]
+    fn zero_cell_content_start_means_65536() {
+        let header = BTreePageHeader::parse(&[0x0d, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(header.cell_content_start, 65536);
+    }
+
+    #[test]
+    fn rejects_cell_pointers_outside_content_area() {
+        let mut page = [0; 64];
+        page[0] = 0x0d;
+        page[4] = 2;
+        page[8..10].copy_from_slice(&9u16.to_be_bytes());
+        page[10..12].copy_from_slice(&61u16.to_be_bytes());
+        let header = BTreePageHeader::parse(&page).unwrap();
+        assert_eq!(
+            header.cell_pointers(&page, 0).collect::<Vec<_>>(),
+            vec![Err(Error::InvalidCellPointer(9)), Err(Error::InvalidCellPointer(61))],
+        );
+    }
+
+    #[test]
+    fn local_payload_sizes() {
+        assert_eq!(max_local_payload(&PageType::LeafTable, 512), 477);
+        assert_eq!(max_local_payload(&PageType::InteriorIndex, 512), 102);
+        assert_eq!(min_local_payload(512), 39);
+        assert_eq!(max_local_payload(&PageType::LeafTable, 0), 0);
+
+        assert_eq!(local_payload(477, 512, 477), 477);
+        // 39 + (1000 - 39) % 508 = 492 is too large, so only M bytes stay local.
+        assert_eq!(local_payload(1000, 512, 477), 39);
+        assert_eq!(local_payload(700, 512, 477), 192);
+    }
+
+    #[test]
+    fn parses_overflowing_cell() {
+        // A 732-byte payload keeps 39 + (732 - 39) % 508 = 224 bytes on the page.
+        let mut cell = vec![0x85, 0x5c, 0x01];
+        cell.extend_from_slice(&[0xaa; 224]);
+        cell.extend_from_slice(&9u32.to_be_bytes());
+        let parsed = parse_table_leaf_cell(&cell, 512, 477).unwrap();
+        assert_eq!(parsed.payload_size, 732);
+        assert_eq!(parsed.rowid, 1);
+        assert_eq!(parsed.local_payload.len(), 224);
+        assert_eq!(parsed.overflow_page, Some(9));
+
+        assert_eq!(
+            parse_table_leaf_cell(&cell[..100], 512, 477).unwrap_err(),
+            Error::TooShort { expected: 227, found: 100 },
+        );
+        assert_eq!(
+            parse_table_leaf_cell(&cell[..229], 512, 477).unwrap_err(),
+            Error::TooShort { expected: 231, found: 229 },
+        );
+    }
+
+    /// Three 512-byte pages, with an overflow chain from page 2 to page 3.
+    fn chain() -> [u8; 1536] {
+        let mut db = [0; 1536];
+        db[512..516].copy_from_slice(&3u32.to_be_bytes());
+        db[516] = 2;
+        db[1028] = 3;
+        db
+    }
+
+    #[test]
+    fn follows_overflow_chain() {
+        let db = chain();
+        let pages = OverflowChain::new(2, &db, 512, 500).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!((pages[0].len(), pages[0][0]), (496, 2));
+        assert_eq!((pages[1].len(), pages[1][0]), (496, 3));
+    }
+
+    #[test]
+    fn overflow_chain_stops_at_cycles_and_bad_pages() {
+        let mut db = chain();
+        db[1024..1028].copy_from_slice(&2u32.to_be_bytes());
+        let last = OverflowChain::new(2, &db, 512, 512).last().unwrap();
+        assert_eq!(last.unwrap_err(), Error::PageCycle(3));
+
+        let mut chain = OverflowChain::new(4, &db, 512, 512);
+        assert_eq!(chain.next().unwrap().unwrap_err(), Error::PageOutOfBounds(4));
+        assert!(chain.next().is_none());
+    }
+}
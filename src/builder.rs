@@ -0,0 +1,184 @@
+use crate::{
+    error::Error, file_format_from_byte, file_format_to_byte, offsets, DatabaseTextEncoding,
+    FileFormat, PageSize, SQLite3Header, Schema, SchemaFormat, Vacuum, VacuumMode,
+};
+
+/// Builds a [`SQLite3Header`] field by field, starting from the header of a new,
+/// empty database as returned by [`SQLite3Header::default`]. The combination of
+/// fields is validated by [`SQLite3HeaderBuilder::build`].
+#[derive(Debug, Clone)]
+pub struct SQLite3HeaderBuilder {
+    page_size: u32,
+    file_format: FileFormat,
+    text_encoding: DatabaseTextEncoding,
+    schema_format: SchemaFormat,
+    application_id: u32,
+    user_version: u32,
+    vacuum_mode: Option<VacuumMode>,
+    largest_root_btree_page: u32,
+}
+
+impl SQLite3HeaderBuilder {
+    pub fn new() -> Self {
+        let header = SQLite3Header::default();
+        Self {
+            page_size: header.page_size_bytes(),
+            file_format: header.file_format_write_version,
+            text_encoding: header.database_text_encoding,
+            schema_format: header.schema.format,
+            application_id: header.application_id,
+            user_version: header.user_version,
+            vacuum_mode: None,
+            largest_root_btree_page: 0,
+        }
+    }
+
+    /// Sets the page size in bytes, which must be a power of two between 512 and
+    /// 65536.
+    pub fn page_size(mut self, bytes: u32) -> Self {
+        self.page_size = bytes;
+        self
+    }
+
+    /// Sets both the file format write and read versions. [`FileFormat::Inaccessible`]
+    /// is rejected by [`SQLite3HeaderBuilder::build`], since SQLite can't read a
+    /// database with that read version.
+    pub fn file_format(mut self, format: FileFormat) -> Self {
+        self.file_format = format;
+        self
+    }
+
+    pub fn text_encoding(mut self, encoding: DatabaseTextEncoding) -> Self {
+        self.text_encoding = encoding;
+        self
+    }
+
+    pub fn schema_format(mut self, format: SchemaFormat) -> Self {
+        self.schema_format = format;
+        self
+    }
+
+    pub fn application_id(mut self, id: u32) -> Self {
+        self.application_id = id;
+        self
+    }
+
+    pub fn user_version(mut self, v: u32) -> Self {
+        self.user_version = v;
+        self
+    }
+
+    /// Enables auto-vacuum in the given mode, or disables it with `None`. Enabling
+    /// auto-vacuum also requires setting the largest root b-tree page.
+    pub fn vacuum_mode(mut self, mode: Option<VacuumMode>) -> Self {
+        self.vacuum_mode = mode;
+        self
+    }
+
+    /// Sets the page number of the largest root b-tree page, which is only stored
+    /// when auto-vacuum is enabled.
+    pub fn largest_root_btree_page(mut self, page: u32) -> Self {
+        self.largest_root_btree_page = page;
+        self
+    }
+
+    /// Validates the fields and builds the header. Auto-vacuum can't be enabled
+    /// without a largest root b-tree page, since a zero page number at offset 52 means
+    /// auto-vacuum is disabled. An inaccessible file format is reported as
+    /// [`Error::UnsupportedFormat`], as parsing such a header would be.
+    pub fn build(&self) -> Result<SQLite3Header, Error> {
+        let page_size = PageSize::new(self.page_size)?;
+        // The format is also the read version, which parsing would reject if it were
+        // inaccessible.
+        file_format_from_byte(
+            offsets::FILE_FORMAT_READ_VERSION,
+            file_format_to_byte(&self.file_format),
+        )?;
+        let vacuum = match self.vacuum_mode {
+            None => None,
+            Some(mode) if self.largest_root_btree_page == 0 => {
+                return Err(Error::InconsistentVacuumState(u32::from(
+                    mode == VacuumMode::Incremental,
+                )))
+            }
+            Some(mode) => Some(Vacuum {
+                largest_root_btree_page: self.largest_root_btree_page,
+                mode,
+            }),
+        };
+
+        let default = SQLite3Header::default();
+        Ok(SQLite3Header {
+            page_size,
+            file_format_write_version: self.file_format,
+            file_format_read_version: self.file_format,
+            schema: Schema {
+                format: self.schema_format,
+                ..default.schema
+            },
+            database_text_encoding: self.text_encoding,
+            user_version: self.user_version,
+            vacuum,
+            application_id: self.application_id,
+            ..default
+        })
+    }
+}
+
+impl Default for SQLite3HeaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_a_parseable_header() {
+        let header = SQLite3HeaderBuilder::new()
+            .page_size(65536)
+            .file_format(FileFormat::WriteAheadLogging)
+            .text_encoding(DatabaseTextEncoding::Utf16le)
+            .schema_format(SchemaFormat::Format1)
+            .application_id(0x0f05_5112)
+            .user_version(3)
+            .vacuum_mode(Some(VacuumMode::Incremental))
+            .largest_root_btree_page(5)
+            .build()
+            .unwrap();
+        assert_eq!(SQLite3Header::from_bytes(&header.to_bytes()), Ok(header.clone()));
+        assert_eq!(header.page_size_bytes(), 65536);
+        assert_eq!(header.file_format_read_version, FileFormat::WriteAheadLogging);
+        assert_eq!(header.schema.format, SchemaFormat::Format1);
+        assert_eq!(header.user_version, 3);
+        assert_eq!(
+            header.vacuum,
+            Some(Vacuum { largest_root_btree_page: 5, mode: VacuumMode::Incremental })
+        );
+    }
+
+    #[test]
+    fn defaults_to_an_empty_database() {
+        assert_eq!(SQLite3HeaderBuilder::default().build(), Ok(SQLite3Header::default()));
+    }
+
+    #[test]
+    fn rejects_invalid_fields() {
+        let builder = SQLite3HeaderBuilder::new();
+        assert_eq!(builder.clone().page_size(1000).build(), Err(Error::InvalidPageSize(1000)));
+        assert_eq!(
+            builder.clone().vacuum_mode(Some(VacuumMode::Auto)).build(),
+            Err(Error::InconsistentVacuumState(0))
+        );
+        assert_eq!(
+            builder.clone().vacuum_mode(Some(VacuumMode::Incremental)).build(),
+            Err(Error::InconsistentVacuumState(1))
+        );
+        assert_eq!(
+            builder.file_format(FileFormat::Inaccessible).build(),
+            Err(Error::UnsupportedFormat { field: "file format read version", value: 3 })
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! The values that the enumerated fields of the 100-byte database header may hold.
+
+/// File format version for rollback journalling, at offsets 18 and 19.
+pub const FILE_FORMAT_LEGACY: u8 = 1;
+/// File format version for WAL journalling, at offsets 18 and 19.
+pub const FILE_FORMAT_WAL: u8 = 2;
+
+/// Schema format number 1, at offset 44.
+pub const SCHEMA_FORMAT_1: u32 = 1;
+/// Schema format number 2, at offset 44.
+pub const SCHEMA_FORMAT_2: u32 = 2;
+/// Schema format number 3, at offset 44.
+pub const SCHEMA_FORMAT_3: u32 = 3;
+/// Schema format number 4, at offset 44.
+pub const SCHEMA_FORMAT_4: u32 = 4;
+
+/// Database text encoding SQLITE_UTF8, at offset 56.
+pub const TEXT_UTF8: u32 = 1;
+/// Database text encoding SQLITE_UTF16LE, at offset 56.
+pub const TEXT_UTF16LE: u32 = 2;
+/// Database text encoding SQLITE_UTF16BE, at offset 56.
+pub const TEXT_UTF16BE: u32 = 3;
+
+/// Incremental-vacuum mode flag for auto_vacuum, at offset 64.
+pub const VACUUM_AUTO: u32 = 0;
+/// Incremental-vacuum mode flag for incremental_vacuum, at offset 64.
+pub const VACUUM_INCREMENTAL: u32 = 1;
+
+/// The page size value at offset 16 that stands for 65536 bytes.
+pub const PAGE_SIZE_65536: u16 = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_match_the_spec() {
+        assert_eq!(FILE_FORMAT_LEGACY, 1);
+        assert_eq!(FILE_FORMAT_WAL, 2);
+        assert_eq!(
+            [SCHEMA_FORMAT_1, SCHEMA_FORMAT_2, SCHEMA_FORMAT_3, SCHEMA_FORMAT_4],
+            [1, 2, 3, 4],
+        );
+        assert_eq!(TEXT_UTF8, 1);
+        assert_eq!(TEXT_UTF16LE, 2);
+        assert_eq!(TEXT_UTF16BE, 3);
+        assert_eq!(VACUUM_AUTO, 0);
+        assert_eq!(VACUUM_INCREMENTAL, 1);
+        assert_eq!(PAGE_SIZE_65536, 1);
+    }
+}
@@ -0,0 +1,100 @@
+//! Deserialization through the parser's validation. Each type here mirrors the
+//! fields of a public type; the public type is deserialized from it with
+//! `#[serde(try_from = ...)]`, so that deserializing can't produce a header that
+//! parsing the same bytes would reject.
+
+use core::convert::TryFrom;
+
+use serde::Deserialize;
+
+use crate::{
+    error::Error, DatabaseTextEncoding, FileFormat, Freelist, LastUpdate, PageSize, Payload,
+    SQLite3Header, Schema, Vacuum, VacuumMode, PAYLOAD_FRACTIONS,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PayloadFields {
+    leaf_fraction: u8,
+    maximum_embedded_fraction: u8,
+    minimum_embedded_fraction: u8,
+}
+
+impl TryFrom<PayloadFields> for Payload {
+    type Error = Error;
+
+    fn try_from(fields: PayloadFields) -> Result<Self, Self::Error> {
+        let found = [
+            fields.maximum_embedded_fraction,
+            fields.minimum_embedded_fraction,
+            fields.leaf_fraction,
+        ];
+        for (&(offset, expected), &found) in PAYLOAD_FRACTIONS.iter().zip(&found) {
+            if found != expected {
+                return Err(Error::InvalidPayloadFraction { offset, expected, found });
+            }
+        }
+        Ok(Payload {
+            leaf_fraction: fields.leaf_fraction,
+            maximum_embedded_fraction: fields.maximum_embedded_fraction,
+            minimum_embedded_fraction: fields.minimum_embedded_fraction,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct HeaderFields {
+    page_size: PageSize,
+    file_format_write_version: FileFormat,
+    file_format_read_version: FileFormat,
+    reserved_bytes_per_page: u8,
+    payload_fraction: Payload,
+    file_change_counter: u32,
+    in_header_database_size: u32,
+    freelist: Freelist,
+    schema: Schema,
+    default_page_cache_size: i32,
+    database_text_encoding: DatabaseTextEncoding,
+    user_version: u32,
+    vacuum: Option<Vacuum>,
+    application_id: u32,
+    #[serde(default)]
+    reserved_expansion: [u8; 20],
+    last_update: LastUpdate,
+}
+
+impl TryFrom<HeaderFields> for SQLite3Header {
+    type Error = Error;
+
+    /// Builds the header and parses its serialized bytes, so that the fields are
+    /// checked exactly as [`SQLite3Header::from_bytes`] checks them.
+    fn try_from(fields: HeaderFields) -> Result<Self, Self::Error> {
+        if let Some(vacuum) = &fields.vacuum {
+            // A zero largest root page is serialized as auto-vacuum being disabled.
+            if vacuum.largest_root_btree_page == 0 {
+                return Err(Error::InconsistentVacuumState(u32::from(
+                    vacuum.mode == VacuumMode::Incremental,
+                )));
+            }
+        }
+
+        let header = SQLite3Header {
+            page_size: fields.page_size,
+            file_format_write_version: fields.file_format_write_version,
+            file_format_read_version: fields.file_format_read_version,
+            reserved_bytes_per_page: fields.reserved_bytes_per_page,
+            payload_fraction: fields.payload_fraction,
+            file_change_counter: fields.file_change_counter,
+            in_header_database_size: fields.in_header_database_size,
+            freelist: fields.freelist,
+            schema: fields.schema,
+            default_page_cache_size: fields.default_page_cache_size,
+            database_text_encoding: fields.database_text_encoding,
+            user_version: fields.user_version,
+            vacuum: fields.vacuum,
+            application_id: fields.application_id,
+            reserved_expansion: fields.reserved_expansion,
+            last_update: fields.last_update,
+        };
+        SQLite3Header::from_bytes(&header.to_bytes())
+    }
+}
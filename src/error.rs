@@ -1,28 +1,345 @@
-use std::{
-    error,
-    fmt::{self, Display, Formatter},
+use core::{
+    array::TryFromSliceError,
+    fmt::{self, Display, Formatter, Write},
 };
+#[cfg(feature = "std")]
+use std::{error, io};
 
 use crate::MAGIC_HEADER_BYTES;
 
 #[derive(Debug)]
 pub enum Error {
-    InvalidMagicHeaderString(String),
-    InvalidPageSize(String),
+    InvalidMagicHeaderString([u8; 16]),
+    InvalidPageSize(u32),
+    InvalidReservedSpace { page_size: u32, reserved: u8 },
+    InvalidPayloadFraction { offset: usize, expected: u8, found: u8 },
+    InvalidFileFormat(u8),
+    InvalidSchemaFormat(u32),
+    UnsupportedFormat { field: &'static str, value: u32 },
+    InvalidTextEncoding(u32),
+    InconsistentVacuumState(u32),
+    NonZeroReserved { offset: usize },
+    InvalidWalMagic(u32),
+    InvalidJournalMagic([u8; 8]),
+    TruncatedVarint,
+    PageOutOfBounds(u32),
+    PageCycle(u32),
+    InvalidFreelistTrunk(u32),
+    InvalidFreelistCount { count: u32, page_count: u32 },
+    InvalidPtrmapType(u8),
+    InvalidPageType(u8),
+    InvalidCell(u32),
+    InvalidCellPointer(u16),
+    InvalidSchemaEntry,
+    InvalidSerialType(u64),
+    InvalidTextEncodingContent,
+    InvalidHex,
+    InvalidSliceLength,
+    TooShort { expected: usize, found: usize },
+    UnexpectedEof { expected: usize, found: usize },
+    #[cfg(feature = "std")]
+    Io(io::Error),
+}
+
+/// Writes `bytes` as a quoted string, replacing invalid UTF-8 sequences with U+FFFD
+/// REPLACEMENT CHARACTER.
+fn write_lossy(f: &mut Formatter, bytes: &[u8]) -> fmt::Result {
+    f.write_char('"')?;
+    let mut rest = bytes;
+    loop {
+        match core::str::from_utf8(rest) {
+            Ok(valid) => {
+                write!(f, "{}", valid.escape_debug())?;
+                return f.write_char('"');
+            }
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                if let Ok(valid) = core::str::from_utf8(valid) {
+                    write!(f, "{}", valid.escape_debug())?;
+                }
+                f.write_char(char::REPLACEMENT_CHARACTER)?;
+                // A sequence cut short by the end of the input is replaced as a whole.
+                rest = &invalid[e.error_len().unwrap_or(invalid.len())..];
+            }
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            Self::InvalidMagicHeaderString(v) => write!(
+            Self::InvalidMagicHeaderString(v) => {
+                write!(f, "expected ")?;
+                write_lossy(f, &MAGIC_HEADER_BYTES)?;
+                write!(f, ", found ")?;
+                write_lossy(f, v)
+            }
+            Self::InvalidPageSize(v) => write!(
                 f,
-                "expected '{}', found {:?}",
-                std::str::from_utf8(&MAGIC_HEADER_BYTES).unwrap(),
+                "invalid page size {}: must be a power of two between 512 and 65536",
                 v,
             ),
-            Self::InvalidPageSize(msg) => write!(f, ""),
+            Self::InvalidReservedSpace { page_size, reserved } => write!(
+                f,
+                "{} reserved bytes leave fewer than 480 usable bytes in a {} byte page",
+                reserved,
+                page_size,
+            ),
+            Self::InvalidPayloadFraction {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "invalid payload fraction at offset {}: expected {}, found {}",
+                offset,
+                expected,
+                found,
+            ),
+            Self::InvalidFileFormat(v) => write!(f, "invalid file format version {}", v),
+            Self::InvalidSchemaFormat(v) => write!(f, "invalid schema format number {}", v),
+            Self::UnsupportedFormat { field, value } => write!(
+                f,
+                "{} {} is from a newer file format than is supported",
+                field,
+                value,
+            ),
+            Self::InvalidTextEncoding(v) => write!(f, "invalid text encoding {}", v),
+            Self::InconsistentVacuumState(v) => write!(
+                f,
+                "incremental vacuum flag is {} but the largest root b-tree page is zero",
+                v,
+            ),
+            Self::NonZeroReserved { offset } => {
+                write!(f, "reserved byte at offset {} is not zero", offset)
+            }
+            Self::InvalidWalMagic(v) => write!(f, "invalid WAL magic number {:#010x}", v),
+            Self::InvalidJournalMagic(v) => write!(f, "invalid journal header {:02x?}", v),
+            Self::TruncatedVarint => write!(f, "input ends in the middle of a varint"),
+            Self::PageOutOfBounds(v) => write!(f, "page {} is outside the database", v),
+            Self::PageCycle(v) => write!(f, "page chain loops back through page {}", v),
+            Self::InvalidFreelistTrunk(v) => {
+                write!(f, "freelist trunk page {} is too small for its leaves", v)
+            }
+            Self::InvalidFreelistCount { count, page_count } => write!(
+                f,
+                "freelist count {} exceeds the {} pages of the database",
+                count,
+                page_count,
+            ),
+            Self::InvalidPtrmapType(v) => write!(f, "invalid ptrmap entry type {}", v),
+            Self::InvalidPageType(v) => write!(f, "invalid b-tree page type {:#04x}", v),
+            Self::InvalidCell(v) => write!(f, "page {} holds a malformed cell", v),
+            Self::InvalidCellPointer(v) => {
+                write!(f, "cell pointer {} is outside the cell content area", v)
+            }
+            Self::InvalidSchemaEntry => write!(f, "malformed sqlite_schema record"),
+            Self::InvalidSerialType(v) => write!(f, "invalid serial type {}", v),
+            Self::InvalidTextEncodingContent => {
+                write!(f, "text is not valid in the database text encoding")
+            }
+            Self::InvalidHex => write!(f, "expected a sequence of hex digit pairs"),
+            Self::InvalidSliceLength => write!(f, "slice is not the length of the array"),
+            Self::TooShort { expected, found } => write!(
+                f,
+                "expected at least {} bytes, found {}",
+                expected,
+                found,
+            ),
+            Self::UnexpectedEof { expected, found } => write!(
+                f,
+                "unexpected end of input after {} of {} bytes",
+                found,
+                expected,
+            ),
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl PartialEq for Error {
+    /// I/O errors compare equal when their kinds match.
+    fn eq(&self, other: &Self) -> bool {
+        match self {
+            Self::InvalidMagicHeaderString(a) => {
+                matches!(other, Self::InvalidMagicHeaderString(b) if a == b)
+            }
+            Self::InvalidPageSize(a) => matches!(other, Self::InvalidPageSize(b) if a == b),
+            Self::InvalidReservedSpace { page_size, reserved } => matches!(
+                other,
+                Self::InvalidReservedSpace { page_size: p, reserved: r }
+                    if page_size == p && reserved == r
+            ),
+            Self::InvalidPayloadFraction {
+                offset,
+                expected,
+                found,
+            } => matches!(
+                other,
+                Self::InvalidPayloadFraction { offset: o, expected: e, found: f }
+                    if offset == o && expected == e && found == f
+            ),
+            Self::InvalidFileFormat(a) => matches!(other, Self::InvalidFileFormat(b) if a == b),
+            Self::InvalidSchemaFormat(a) => {
+                matches!(other, Self::InvalidSchemaFormat(b) if a == b)
+            }
+            Self::UnsupportedFormat { field, value } => matches!(
+                other,
+                Self::UnsupportedFormat { field: f, value: v } if field == f && value == v
+            ),
+            Self::InvalidTextEncoding(a) => {
+                matches!(other, Self::InvalidTextEncoding(b) if a == b)
+            }
+            Self::InconsistentVacuumState(a) => {
+                matches!(other, Self::InconsistentVacuumState(b) if a == b)
+            }
+            Self::NonZeroReserved { offset } => {
+                matches!(other, Self::NonZeroReserved { offset: o } if offset == o)
+            }
+            Self::InvalidWalMagic(a) => matches!(other, Self::InvalidWalMagic(b) if a == b),
+            Self::InvalidJournalMagic(a) => {
+                matches!(other, Self::InvalidJournalMagic(b) if a == b)
+            }
+            Self::TruncatedVarint => matches!(other, Self::TruncatedVarint),
+            Self::PageOutOfBounds(a) => matches!(other, Self::PageOutOfBounds(b) if a == b),
+            Self::PageCycle(a) => matches!(other, Self::PageCycle(b) if a == b),
+            Self::InvalidFreelistTrunk(a) => {
+                matches!(other, Self::InvalidFreelistTrunk(b) if a == b)
+            }
+            Self::InvalidFreelistCount { count, page_count } => matches!(
+                other,
+                Self::InvalidFreelistCount { count: c, page_count: p }
+                    if count == c && page_count == p
+            ),
+            Self::InvalidPtrmapType(a) => matches!(other, Self::InvalidPtrmapType(b) if a == b),
+            Self::InvalidPageType(a) => matches!(other, Self::InvalidPageType(b) if a == b),
+            Self::InvalidCell(a) => matches!(other, Self::InvalidCell(b) if a == b),
+            Self::InvalidCellPointer(a) => matches!(other, Self::InvalidCellPointer(b) if a == b),
+            Self::InvalidSchemaEntry => matches!(other, Self::InvalidSchemaEntry),
+            Self::InvalidSerialType(a) => matches!(other, Self::InvalidSerialType(b) if a == b),
+            Self::InvalidTextEncodingContent => matches!(other, Self::InvalidTextEncodingContent),
+            Self::InvalidHex => matches!(other, Self::InvalidHex),
+            Self::InvalidSliceLength => matches!(other, Self::InvalidSliceLength),
+            Self::TooShort { expected, found } => matches!(
+                other,
+                Self::TooShort { expected: e, found: f } if expected == e && found == f
+            ),
+            Self::UnexpectedEof { expected, found } => matches!(
+                other,
+                Self::UnexpectedEof { expected: e, found: f } if expected == e && found == f
+            ),
+            #[cfg(feature = "std")]
+            Self::Io(a) => matches!(other, Self::Io(b) if a.kind() == b.kind()),
         }
     }
 }
 
-impl error::Error for Error {}
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A failed slice to array conversion doesn't record the lengths involved, so where
+/// they are known, [`Error::TooShort`] is more useful.
+impl From<TryFromSliceError> for Error {
+    fn from(_: TryFromSliceError) -> Self {
+        Self::InvalidSliceLength
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn display() {
+        use alloc::string::ToString;
+
+        assert_eq!(
+            Error::InvalidPageSize(1000).to_string(),
+            "invalid page size 1000: must be a power of two between 512 and 65536",
+        );
+        assert_eq!(
+            Error::TooShort { expected: 100, found: 12 }.to_string(),
+            "expected at least 100 bytes, found 12",
+        );
+        assert_eq!(
+            Error::InvalidReservedSpace { page_size: 512, reserved: 40 }.to_string(),
+            "40 reserved bytes leave fewer than 480 usable bytes in a 512 byte page",
+        );
+        assert_eq!(
+            Error::InvalidWalMagic(0x1234).to_string(),
+            "invalid WAL magic number 0x00001234"
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn display_magic_header_string_lossily() {
+        use alloc::string::ToString;
+
+        let mut found = *b"SQLite format 2\0";
+        found[0] = 0xff;
+        assert_eq!(
+            Error::InvalidMagicHeaderString(found).to_string(),
+            "expected \"SQLite format 3\\0\", found \"\u{fffd}QLite format 2\\0\"",
+        );
+        assert_eq!(
+            Error::InvalidMagicHeaderString(*b"SQL\xe2\x82ite form\0\xf0\x9f").to_string(),
+            "expected \"SQLite format 3\\0\", found \"SQL\u{fffd}ite form\\0\u{fffd}\"",
+        );
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(Error::PageCycle(3), Error::PageCycle(3));
+        assert_ne!(Error::PageCycle(3), Error::PageCycle(4));
+        assert_ne!(Error::PageCycle(3), Error::PageOutOfBounds(3));
+        assert_eq!(Error::InvalidHex, Error::InvalidHex);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_errors_compare_by_kind() {
+        use std::error::Error as _;
+
+        let a = Error::Io(io::Error::new(io::ErrorKind::NotFound, "a"));
+        let b = Error::Io(io::Error::new(io::ErrorKind::NotFound, "b"));
+        assert_eq!(a, b);
+        assert_ne!(a, Error::Io(io::ErrorKind::PermissionDenied.into()));
+        assert!(a.source().is_some());
+        assert!(Error::InvalidHex.source().is_none());
+    }
+
+    #[test]
+    fn converts_slice_errors() {
+        fn convert(bytes: &[u8]) -> Result<[u8; 4], Error> {
+            Ok(core::convert::TryInto::try_into(bytes)?)
+        }
+        assert_eq!(convert(&[1, 2, 3, 4]), Ok([1, 2, 3, 4]));
+        assert_eq!(convert(&[1, 2, 3]), Err(Error::InvalidSliceLength));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn converts_io_errors() {
+        fn open() -> Result<std::fs::File, Error> {
+            Ok(std::fs::File::open("/nonexistent/database.sqlite")?)
+        }
+        assert!(matches!(open(), Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound));
+    }
+}
@@ -0,0 +1,108 @@
+// https://sqlite.org/fileformat2.html#the_rollback_journal
+
+use crate::{error::Error, four_byte_slice_to_u32};
+
+/// The journal header fields occupy the first 28 bytes of the rollback journal. The
+/// header is padded out to the sector size with zeros.
+pub const JOURNAL_HEADER_SIZE: usize = 28;
+
+/// The header string: 0xd9, 0xd5, 0x05, 0xf9, 0x20, 0xa1, 0x63, 0xd7
+const MAGIC_JOURNAL_BYTES: [u8; 8] = [0xd9, 0xd5, 0x05, 0xf9, 0x20, 0xa1, 0x63, 0xd7];
+
+/// The page count of a journal whose pages run to the end of the file.
+const PAGE_COUNT_TO_END_OF_FILE: u32 = 0xffffffff;
+
+/// The rollback journal is a file associated with each SQLite database file that
+/// holds information used to restore the database file to its initial state during
+/// the course of a transaction. A valid rollback journal begins with a header in
+/// the following format; all integers are big-endian.
+#[derive(Debug)]
+pub struct JournalHeader {
+    /// Number of pages in the next segment of the journal, or -1 to mean all content
+    /// to the end of the file.
+    pub page_count: u32,
+    /// A random nonce for the checksum.
+    pub nonce: u32,
+    /// Initial size of the database in pages.
+    pub initial_database_size: u32,
+    /// Size of a disk sector assumed by the process that wrote this journal.
+    pub sector_size: u32,
+    /// Size of pages in this journal.
+    pub page_size: u32,
+}
+
+impl JournalHeader {
+    /// Parses the first 28 bytes of `bytes` as a rollback journal header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < JOURNAL_HEADER_SIZE {
+            return Err(Error::TooShort {
+                expected: JOURNAL_HEADER_SIZE,
+                found: bytes.len(),
+            });
+        }
+
+        let mut magic = [0; 8];
+        magic.copy_from_slice(&bytes[0..8]);
+        if magic != MAGIC_JOURNAL_BYTES {
+            return Err(Error::InvalidJournalMagic(magic));
+        }
+
+        Ok(Self {
+            page_count: four_byte_slice_to_u32(&bytes[8..12])?,
+            nonce: four_byte_slice_to_u32(&bytes[12..16])?,
+            initial_database_size: four_byte_slice_to_u32(&bytes[16..20])?,
+            sector_size: four_byte_slice_to_u32(&bytes[20..24])?,
+            page_size: four_byte_slice_to_u32(&bytes[24..28])?,
+        })
+    }
+
+    /// Whether the page count is -1, meaning the journal's pages run to the end of the
+    /// file rather than a fixed count.
+    pub fn runs_to_end_of_file(&self) -> bool {
+        self.page_count == PAGE_COUNT_TO_END_OF_FILE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_header(page_count: u32) -> [u8; JOURNAL_HEADER_SIZE] {
+        let mut bytes = [0; JOURNAL_HEADER_SIZE];
+        bytes[0..8].copy_from_slice(&MAGIC_JOURNAL_BYTES);
+        for (chunk, value) in bytes[8..].chunks_exact_mut(4).zip(&[page_count, 7, 23, 512, 4096]) {
+            chunk.copy_from_slice(&value.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_header() {
+        let header = JournalHeader::from_bytes(&journal_header(3)).unwrap();
+        assert_eq!(header.page_count, 3);
+        assert_eq!(header.nonce, 7);
+        assert_eq!(header.initial_database_size, 23);
+        assert_eq!(header.sector_size, 512);
+        assert_eq!(header.page_size, 4096);
+        assert!(!header.runs_to_end_of_file());
+
+        let header = JournalHeader::from_bytes(&journal_header(u32::MAX)).unwrap();
+        assert!(header.runs_to_end_of_file());
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut bytes = journal_header(0);
+        assert_eq!(
+            JournalHeader::from_bytes(&bytes[..27]).unwrap_err(),
+            Error::TooShort { expected: 28, found: 27 },
+        );
+        bytes[0] = 0;
+        let mut magic = MAGIC_JOURNAL_BYTES;
+        magic[0] = 0;
+        assert_eq!(
+            JournalHeader::from_bytes(&bytes).unwrap_err(),
+            Error::InvalidJournalMagic(magic)
+        );
+    }
+}
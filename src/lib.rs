@@ -1,8 +1,59 @@
 // https://sqlite.org/fileformat2.html
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+mod borrowed;
+mod builder;
+#[cfg(feature = "serde")]
+mod de;
+pub mod btree;
+pub mod codes;
 pub mod error;
+pub mod journal;
+pub mod offsets;
+#[cfg(feature = "alloc")]
+pub mod record;
+#[cfg(feature = "alloc")]
+pub mod schema;
+pub mod vacuum;
+pub mod varint;
+pub mod wal;
+
+pub use borrowed::SQLite3HeaderRef;
+pub use builder::SQLite3HeaderBuilder;
+pub use varint::read_varint;
+
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    num::NonZeroU32,
+    str::FromStr,
+};
+#[cfg(feature = "alloc")]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
 
-use std::convert::TryInto;
+use error::Error;
+
+/// The byte offset of the lock-byte page, the page that SQLite uses for file locking
+/// and never stores data in: 2^30, or 1 GiB.
+const PENDING_BYTE: u32 = 0x4000_0000;
 
 /// The C string "SQLite format 3\000"
 const MAGIC_HEADER_BYTES: [u8; 16] = [
@@ -12,12 +63,254 @@ const MAGIC_HEADER_BYTES: [u8; 16] = [
     0x74, 0x20, 0x33, 0x00,
 ];
 
-fn two_byte_slice_to_u16(slice: &[u8]) -> u16 {
-    u16::from_be_bytes(slice.try_into().unwrap())
+/// The database header is the first 100 bytes of the database file.
+const HEADER_SIZE: usize = offsets::HEADER_LEN;
+
+/// The usable size of a page is not allowed to be less than 480 bytes.
+const MINIMUM_USABLE_SIZE: u32 = 480;
+
+/// The offsets of the maximum embedded, minimum embedded, and leaf payload fractions
+/// and the only values they may hold.
+const PAYLOAD_FRACTIONS: [(usize, u8); 3] = [
+    (offsets::MAXIMUM_EMBEDDED_PAYLOAD_FRACTION, 64),
+    (offsets::MINIMUM_EMBEDDED_PAYLOAD_FRACTION, 32),
+    (offsets::LEAF_PAYLOAD_FRACTION, 32),
+];
+
+/// The newest SQLite release whose file format this library was checked against.
+/// Headers created by this library record it as the SQLITE_VERSION_NUMBER.
+pub const SUPPORTED_SQLITE_VERSION: SqliteVersion = SqliteVersion {
+    major: 3,
+    minor: 45,
+    patch: 0,
+};
+
+/// The appendvfs extension ends the outer file with this prefix followed by the
+/// 8-byte big-endian offset at which the appended database begins.
+const APPEND_MARK: &[u8; 17] = b"Start-Of-SQLite3-";
+
+/// The size of the appendvfs trailer: the mark followed by the 8-byte offset.
+const APPEND_TRAILER_SIZE: usize = APPEND_MARK.len() + 8;
+
+/// The reserved space per page used by the SQLite Encryption Extension's AES modes
+/// for each page's nonce, which leave the database header in plaintext.
+const SEE_RESERVED_BYTES: u8 = 16;
+
+/// Well-known application IDs, from the magic.txt file in the SQLite source
+/// repository.
+const APPLICATION_IDS: [(u32, &str); 10] = [
+    (0x0f055111, "Fossil repository"),
+    (0x0f055112, "Fossil checkout"),
+    (0x0f055113, "Fossil global configuration"),
+    (0x42654462, "Bentley Systems BeSQLite Database"),
+    (0x42654c6e, "Bentley Systems Localization File"),
+    (0x45737269, "Esri Spatially-Enabled Database"),
+    (0x47503130, "GeoPackage 1.0"),
+    (0x47503131, "GeoPackage 1.1"),
+    (0x47504b47, "GeoPackage"),
+    (0x4d504258, "MBTiles tileset"),
+];
+
+fn two_byte_slice_to_u16(slice: &[u8]) -> Result<u16, Error> {
+    let bytes = slice.try_into().map_err(|_| Error::TooShort {
+        expected: 2,
+        found: slice.len(),
+    })?;
+    Ok(u16::from_be_bytes(bytes))
+}
+
+fn four_byte_slice_to_u32(slice: &[u8]) -> Result<u32, Error> {
+    let bytes = slice.try_into().map_err(|_| Error::TooShort {
+        expected: 4,
+        found: slice.len(),
+    })?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn four_byte_slice_to_i32(slice: &[u8]) -> Result<i32, Error> {
+    let bytes = slice.try_into().map_err(|_| Error::TooShort {
+        expected: 4,
+        found: slice.len(),
+    })?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+/// Labels every header field in `bytes` with its byte range, its name, and its
+/// decoded value, for rendering an annotated hex dump. Values are decoded without
+/// validation. Fields that extend past the end of `bytes` are omitted.
+#[cfg(feature = "alloc")]
+pub fn annotate(bytes: &[u8]) -> Vec<(Range<usize>, &'static str, String)> {
+    offsets::FIELDS
+        .iter()
+        .filter_map(|&(name, offset, len)| {
+            let value = field(bytes, offset, len).ok()?;
+            let decoded = match offset {
+                offsets::MAGIC_HEADER_STRING => format!("{:?}", String::from_utf8_lossy(value)),
+                offsets::PAGE_SIZE => page_size_to_bytes(u16_at(bytes, offset).ok()?).to_string(),
+                offsets::DEFAULT_PAGE_CACHE_SIZE => i32_at(bytes, offset).ok()?.to_string(),
+                offsets::DATABASE_TEXT_ENCODING => {
+                    let v = u32_at(bytes, offset).ok()?;
+                    match text_encoding_from_u32(v) {
+                        Ok(encoding) => encoding.to_string(),
+                        Err(_) => v.to_string(),
+                    }
+                }
+                offsets::RESERVED => value.iter().map(|b| format!("{:02x}", b)).collect(),
+                _ => match len {
+                    1 => value[0].to_string(),
+                    _ => u32_at(bytes, offset).ok()?.to_string(),
+                },
+            };
+            Some((offset..offset + len, name, decoded))
+        })
+        .collect()
+}
+
+/// Looks for the trailer written by SQLite's appendvfs extension at the end of
+/// `bytes`, the contents of an outer file with a database appended to it, and
+/// returns the offset at which the appended database begins. The offset can be
+/// passed to [`SQLite3Header::from_bytes_at`]. Returns `None` if there is no trailer
+/// or if it points outside of the outer file.
+pub fn find_appended_database(bytes: &[u8]) -> Option<u64> {
+    let trailer = bytes.len().checked_sub(APPEND_TRAILER_SIZE)?;
+    let (mark, offset) = bytes[trailer..].split_at(APPEND_MARK.len());
+    if mark != APPEND_MARK {
+        return None;
+    }
+    let offset = u64::from_be_bytes(offset.try_into().ok()?);
+    if offset >= trailer as u64 {
+        return None;
+    }
+    Some(offset)
+}
+
+/// The entry point for fuzzing the parser, used by the target in `fuzz/`. It parses
+/// `data` like [`SQLite3Header::from_bytes`], and any input, however malformed, must
+/// produce an `Err` rather than a panic.
+pub fn parse_fuzz(data: &[u8]) -> Result<SQLite3Header, Error> {
+    SQLite3Header::from_bytes(data)
+}
+
+/// Reads only the page size and text encoding of the header at the start of
+/// `bytes`, for classifying many files cheaply. The length and magic header string
+/// are checked, as are those two fields, but no other field is decoded.
+pub fn peek(bytes: &[u8]) -> Result<(PageSize, DatabaseTextEncoding), Error> {
+    check_length_and_magic(bytes)?;
+    let page_size = PageSize::from_on_disk(u16_at(bytes, offsets::PAGE_SIZE)?)?;
+    let encoding = text_encoding_from_u32(u32_at(bytes, offsets::DATABASE_TEXT_ENCODING)?)?;
+    Ok((page_size, encoding))
+}
+
+/// Reads a big-endian integer at `offset` into `bytes`.
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, Error> {
+    two_byte_slice_to_u16(field(bytes, offset, 2)?)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, Error> {
+    four_byte_slice_to_u32(field(bytes, offset, 4)?)
+}
+
+fn i32_at(bytes: &[u8], offset: usize) -> Result<i32, Error> {
+    four_byte_slice_to_i32(field(bytes, offset, 4)?)
+}
+
+fn field(bytes: &[u8], offset: usize, len: usize) -> Result<&[u8], Error> {
+    bytes.get(offset..offset + len).ok_or(Error::TooShort {
+        expected: offset + len,
+        found: bytes.len(),
+    })
+}
+
+/// Checks that `bytes` holds at least a whole header and begins with the magic
+/// header string.
+fn check_length_and_magic(bytes: &[u8]) -> Result<(), Error> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(Error::TooShort {
+            expected: HEADER_SIZE,
+            found: bytes.len(),
+        });
+    }
+
+    let mut magic = [0; offsets::MAGIC_HEADER_STRING_LEN];
+    magic.copy_from_slice(field(
+        bytes,
+        offsets::MAGIC_HEADER_STRING,
+        offsets::MAGIC_HEADER_STRING_LEN,
+    )?);
+    if magic != MAGIC_HEADER_BYTES {
+        return Err(Error::InvalidMagicHeaderString(magic));
+    }
+    Ok(())
+}
+
+/// Returns page `page_number` of the database `db`. Pages are numbered beginning
+/// with 1.
+fn page(db: &[u8], page_number: u32, page_size: u32) -> Result<&[u8], Error> {
+    let page_size = page_size as usize;
+    let start = (page_number as usize)
+        .checked_sub(1)
+        .and_then(|index| index.checked_mul(page_size))
+        .ok_or(Error::PageOutOfBounds(page_number))?;
+    db.get(start..start + page_size)
+        .ok_or(Error::PageOutOfBounds(page_number))
+}
+
+/// Resolves the on-disk page size field, where the value 1 stands for 65536.
+fn page_size_to_bytes(page_size: u16) -> u32 {
+    match page_size {
+        codes::PAGE_SIZE_65536 => 65536,
+        v => u32::from(v),
+    }
+}
+
+/// Decodes the file format version byte `value` found at `offset`, which is that of
+/// either the write or the read version. A read version above 2 means the database
+/// uses a newer file format than this library models and can't be read at all, so it
+/// is reported as [`Error::UnsupportedFormat`]. A write version above 2 only makes
+/// the database read-only, and is decoded as [`FileFormat::Inaccessible`].
+fn file_format_from_byte(offset: usize, value: u8) -> Result<FileFormat, Error> {
+    match value {
+        codes::FILE_FORMAT_LEGACY => Ok(FileFormat::Legacy),
+        codes::FILE_FORMAT_WAL => Ok(FileFormat::WriteAheadLogging),
+        0 => Err(Error::InvalidFileFormat(value)),
+        _ if offset == offsets::FILE_FORMAT_READ_VERSION => Err(Error::UnsupportedFormat {
+            field: "file format read version",
+            value: u32::from(value),
+        }),
+        _ => Ok(FileFormat::Inaccessible),
+    }
+}
+
+fn schema_format_from_u32(value: u32) -> Result<SchemaFormat, Error> {
+    match value {
+        codes::SCHEMA_FORMAT_1 => Ok(SchemaFormat::Format1),
+        codes::SCHEMA_FORMAT_2 => Ok(SchemaFormat::Format2),
+        codes::SCHEMA_FORMAT_3 => Ok(SchemaFormat::Format3),
+        codes::SCHEMA_FORMAT_4 => Ok(SchemaFormat::Format4),
+        0 => Err(Error::InvalidSchemaFormat(value)),
+        _ => Err(Error::UnsupportedFormat {
+            field: "schema format",
+            value,
+        }),
+    }
+}
+
+fn text_encoding_from_u32(value: u32) -> Result<DatabaseTextEncoding, Error> {
+    match value {
+        codes::TEXT_UTF8 => Ok(DatabaseTextEncoding::Utf8),
+        codes::TEXT_UTF16LE => Ok(DatabaseTextEncoding::Utf16le),
+        codes::TEXT_UTF16BE => Ok(DatabaseTextEncoding::Utf16be),
+        _ => Err(Error::InvalidTextEncoding(value)),
+    }
 }
 
-fn four_byte_slice_to_u32(slice: &[u8]) -> u32 {
-    u32::from_be_bytes(slice.try_into().unwrap())
+fn file_format_to_byte(format: &FileFormat) -> u8 {
+    match format {
+        FileFormat::Legacy => codes::FILE_FORMAT_LEGACY,
+        FileFormat::WriteAheadLogging => codes::FILE_FORMAT_WAL,
+        // Any version above 2 is inaccessible; the lowest such version is written.
+        FileFormat::Inaccessible => 3,
+    }
 }
 
 /// The file format write version and file format read version at offsets 18 and 19
@@ -29,20 +322,129 @@ fn four_byte_slice_to_u32(slice: &[u8]) -> u32 {
 /// database file must be treated as read-only. If a database file with a read
 /// version greater than 2 is encountered, then that database cannot be read or
 /// written.
-#[derive(Debug)]
+///
+/// Parsing rejects a read version greater than 2 with [`Error::UnsupportedFormat`],
+/// so only the write version of a parsed header can be `Inaccessible`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FileFormat {
+    /// A write version greater than 2, which makes the database read-only.
     Inaccessible,
     Legacy,
     WriteAheadLogging,
 }
 
+impl fmt::Display for FileFormat {
+    /// Renders the journalling mode the version stands for: `legacy` for rollback
+    /// journalling, `WAL`, or `inaccessible`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Legacy => "legacy",
+            Self::WriteAheadLogging => "WAL",
+            Self::Inaccessible => "inaccessible",
+        })
+    }
+}
+
+/// The journal mode of a database, as implied by its file format versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JournalMode {
+    /// A rollback journal, with both file format versions 1.
+    Rollback,
+    /// A write-ahead log, with both file format versions 2.
+    Wal,
+}
+
+/// A database page size: a power of two between 512 and 65536 bytes, inclusive.
+/// On disk it is stored as a two-byte big-endian integer, with the value 1 standing
+/// for 65536, which does not fit in two bytes. With the `serde` feature it is
+/// serialized as the page size in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+pub struct PageSize(u16);
+
+impl PageSize {
+    /// A page size of `bytes` bytes, which must be a power of two between 512 and
+    /// 65536.
+    pub fn new(bytes: u32) -> Result<Self, Error> {
+        if !bytes.is_power_of_two() || !(512..=65536).contains(&bytes) {
+            return Err(Error::InvalidPageSize(bytes));
+        }
+        Ok(Self(if bytes == 65536 {
+            codes::PAGE_SIZE_65536
+        } else {
+            bytes as u16
+        }))
+    }
+
+    /// The page size stored on disk as `value`, where 1 stands for 65536.
+    pub fn from_on_disk(value: u16) -> Result<Self, Error> {
+        Self::new(page_size_to_bytes(value))
+    }
+
+    /// The page size in bytes.
+    pub fn bytes(&self) -> u32 {
+        page_size_to_bytes(self.0)
+    }
+
+    /// The page size as stored on disk, where 1 stands for 65536.
+    pub fn on_disk(&self) -> u16 {
+        self.0
+    }
+}
+
+impl TryFrom<u16> for PageSize {
+    type Error = Error;
+
+    /// Converts an on-disk page size, like [`PageSize::from_on_disk`].
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Self::from_on_disk(value)
+    }
+}
+
+impl From<PageSize> for u16 {
+    fn from(page_size: PageSize) -> Self {
+        page_size.on_disk()
+    }
+}
+
+impl TryFrom<u32> for PageSize {
+    type Error = Error;
+
+    /// Converts a page size in bytes, like [`PageSize::new`].
+    fn try_from(bytes: u32) -> Result<Self, Self::Error> {
+        Self::new(bytes)
+    }
+}
+
+impl From<PageSize> for u32 {
+    fn from(page_size: PageSize) -> Self {
+        page_size.bytes()
+    }
+}
+
+impl fmt::Display for PageSize {
+    /// Renders the page size in bytes.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.bytes())
+    }
+}
+
 /// The maximum and minimum embedded payload fractions and the leaf payload
 /// fraction values must be 64, 32, and 32. These values were originally intended
 /// to be tunable parameters that could be used to modify the storage format of the
 /// b-tree algorithm. However, that functionality is not supported and there are no
 /// current plans to add support in the future. Hence, these three bytes are fixed
 /// at the values specified.
-#[derive(Debug)]
+///
+/// A `Payload` can only be obtained from a parsed header, so its values are always
+/// the fixed ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "de::PayloadFields"))]
+#[non_exhaustive]
 pub struct Payload {
     pub leaf_fraction: u8,
     pub maximum_embedded_fraction: u8,
@@ -53,12 +455,133 @@ pub struct Payload {
 /// big-endian integer at offset 32 stores the page number of the first page of the
 /// freelist, or zero if the freelist is empty. The 4-byte big-endian integer at
 /// offset 36 stores stores the total number of pages on the freelist.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Freelist {
     pub page_index: u32,
     pub count: u32,
 }
 
+impl Freelist {
+    /// The page number of the first freelist trunk page, or `None` if the freelist is
+    /// empty.
+    pub fn first_page(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.page_index)
+    }
+
+    /// Whether there are no pages on the freelist.
+    pub fn is_empty(&self) -> bool {
+        self.page_index == 0
+    }
+
+    /// Checks that the freelist fits in a database of `page_count` pages: its first
+    /// trunk page must be one of those pages and it can't hold more pages than that.
+    /// This catches corrupt freelist metadata before [`Freelist::pages`] is used.
+    pub fn validate_against(&self, page_count: u32) -> Result<(), Error> {
+        if self.page_index > page_count {
+            return Err(Error::PageOutOfBounds(self.page_index));
+        }
+        if self.count > page_count {
+            return Err(Error::InvalidFreelistCount {
+                count: self.count,
+                page_count,
+            });
+        }
+        Ok(())
+    }
+
+    /// Iterates over the page numbers of every page on the freelist, given the bytes of
+    /// the whole database. The freelist is organized as a linked list of freelist
+    /// trunk pages with each trunk page containing page numbers for zero or more
+    /// freelist leaf pages. Each trunk page is yielded followed by its leaves. At most
+    /// `count` pages are yielded, which guards against cycles in a corrupt freelist.
+    /// A trunk page may hold at most `usable_size / 4 - 2` leaf page numbers, where the
+    /// usable size excludes the reserved space given by byte 20 of `db`; a trunk page
+    /// claiming more, or one too small to hold its two header integers, is reported
+    /// as [`Error::InvalidFreelistTrunk`].
+    pub fn pages<'a>(&self, db: &'a [u8], page_size: u32) -> FreelistPages<'a> {
+        let reserved = db.get(offsets::RESERVED_BYTES_PER_PAGE).copied().unwrap_or(0);
+        FreelistPages {
+            db,
+            page_size,
+            usable_size: page_size.saturating_sub(u32::from(reserved)),
+            next_trunk: self.page_index,
+            trunk: &[],
+            leaves: 0,
+            remaining: self.count,
+        }
+    }
+}
+
+/// An iterator over the pages of a freelist, created by [`Freelist::pages`].
+#[derive(Debug)]
+pub struct FreelistPages<'a> {
+    db: &'a [u8],
+    page_size: u32,
+    usable_size: u32,
+    next_trunk: u32,
+    /// The leaf page numbers of the current trunk page that are yet to be yielded.
+    trunk: &'a [u8],
+    leaves: usize,
+    remaining: u32,
+}
+
+impl FreelistPages<'_> {
+    fn next_page(&mut self) -> Result<Option<u32>, Error> {
+        if self.leaves > 0 {
+            let leaf = four_byte_slice_to_u32(&self.trunk[..4])?;
+            self.trunk = &self.trunk[4..];
+            self.leaves -= 1;
+            return Ok(Some(leaf));
+        }
+
+        if self.next_trunk == 0 {
+            return Ok(None);
+        }
+
+        // The first integer on a freelist trunk page is the page number of the next
+        // freelist trunk page in the list or zero if this is the last freelist trunk
+        // page. The second integer on a freelist trunk page is the number of leaf page
+        // pointers to follow.
+        let trunk_number = self.next_trunk;
+        let trunk = page(self.db, trunk_number, self.page_size)?;
+        let trunk = &trunk[..trunk.len().min(self.usable_size as usize)];
+        if trunk.len() < 8 {
+            return Err(Error::InvalidFreelistTrunk(trunk_number));
+        }
+        self.next_trunk = four_byte_slice_to_u32(&trunk[0..4])?;
+        let leaves = four_byte_slice_to_u32(&trunk[4..8])? as usize;
+        if leaves > (trunk.len() - 8) / 4 {
+            return Err(Error::InvalidFreelistTrunk(trunk_number));
+        }
+        self.trunk = &trunk[8..8 + leaves * 4];
+        self.leaves = leaves;
+        Ok(Some(trunk_number))
+    }
+}
+
+impl Iterator for FreelistPages<'_> {
+    type Item = Result<u32, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match self.next_page() {
+            Ok(Some(page)) => {
+                self.remaining -= 1;
+                Some(Ok(page))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 /// The schema format number is a 4-byte big-endian integer at offset 44. The
 /// schema format number is similar to the file format read and write version
 /// numbers at offsets 18 and 19 except that the schema format number refers to the
@@ -68,11 +591,13 @@ pub struct Freelist {
 /// 2. Format 2 adds the ability of rows within the same table to have a varying number of columns, in order to support the ALTER TABLE ... ADD COLUMN functionality. Support for reading and writing format 2 was added in SQLite version 3.1.3 on 2005-02-20.
 /// 3. Format 3 adds the ability of extra columns added by ALTER TABLE ... ADD COLUMN to have non-NULL default values. This capability was added in SQLite version 3.1.4 on 2005-03-11.
 /// 4. Format 4 causes SQLite to respect the DESC keyword on index declarations. (The DESC keyword is ignored in indexes for formats 1, 2, and 3.) Format 4 also adds two new boolean record type values (serial types 8 and 9). Support for format 4 was added in SQLite 3.3.0 on 2006-01-10.
+///
 /// New database files created by SQLite use format 4 by default. The
 /// legacy_file_format pragma can be used to cause SQLite to create new database
 /// files using format 1. The format version number can be made to default to 1
 /// instead of 4 by setting SQLITE_DEFAULT_FILE_FORMAT=1 at compile-time.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SchemaFormat {
     Format1,
     Format2,
@@ -80,7 +605,33 @@ pub enum SchemaFormat {
     Format4,
 }
 
-#[derive(Debug)]
+impl SchemaFormat {
+    /// The schema format number as stored at offset 44.
+    pub fn number(&self) -> u8 {
+        match self {
+            SchemaFormat::Format1 => 1,
+            SchemaFormat::Format2 => 2,
+            SchemaFormat::Format3 => 3,
+            SchemaFormat::Format4 => 4,
+        }
+    }
+
+    /// Whether SQLite respects the DESC keyword on index declarations, which it only
+    /// does for format 4.
+    pub fn respects_desc_indexes(&self) -> bool {
+        matches!(self, SchemaFormat::Format4)
+    }
+}
+
+impl fmt::Display for SchemaFormat {
+    /// Renders the schema format number, e.g. `4`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Schema {
     /// The schema cookie is a 4-byte big-endian integer at offset 40 that is
     /// incremented whenever the database schema changes. A prepared statement is
@@ -99,19 +650,48 @@ pub struct Schema {
 /// UTF-16be. No other values are allowed. The sqlite3.h header file defines C-preprocessor macros
 /// SQLITE_UTF8 as 1, SQLITE_UTF16LE as 2, and SQLITE_UTF16BE as 3, to use in place of the numeric
 /// codes for the text encoding.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DatabaseTextEncoding {
     Utf8,
     Utf16le,
     Utf16be,
 }
 
-#[derive(Debug)]
+impl DatabaseTextEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16le => "UTF-16le",
+            Self::Utf16be => "UTF-16be",
+        }
+    }
+}
+
+impl fmt::Display for DatabaseTextEncoding {
+    /// Renders the encoding as SQLite names it, e.g. `UTF-8` or `UTF-16le`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VacuumMode {
     Auto,
     Incremental,
 }
 
+impl fmt::Display for VacuumMode {
+    /// Renders the mode as `auto_vacuum` or `incremental_vacuum`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto_vacuum",
+            Self::Incremental => "incremental_vacuum",
+        })
+    }
+}
+
 /// The two 4-byte big-endian integers at offsets 52 and 64 are used to manage the
 /// auto_vacuum and incremental_vacuum modes. If the integer at offset 52 is zero
 /// then pointer-map (ptrmap) pages are omitted from the database file and neither
@@ -121,27 +701,300 @@ pub enum VacuumMode {
 /// auto_vacuum or incremental_vacuum. In this latter case, the integer at offset 64
 /// is true for incremental_vacuum and false for auto_vacuum. If the integer at
 /// offset 52 is zero then the integer at offset 64 must also be zero.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vacuum {
     pub largest_root_btree_page: u32,
     pub mode: VacuumMode,
 }
 
+/// A version of the SQLite library, as encoded in SQLITE_VERSION_NUMBER. Versions
+/// are ordered by major, then minor, then patch number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SqliteVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+
+impl SqliteVersion {
+    /// Decodes a SQLITE_VERSION_NUMBER, which is `major * 1000000 + minor * 1000 +
+    /// patch`. A version number of 0 decodes to 0.0.0.
+    pub fn from_number(v: u32) -> Self {
+        Self {
+            major: (v / 1_000_000) as u16,
+            minor: (v / 1000 % 1000) as u16,
+            patch: (v % 1000) as u16,
+        }
+    }
+
+    /// Encodes the version as a SQLITE_VERSION_NUMBER, saturating at `u32::MAX` for
+    /// major versions too large to encode.
+    pub fn to_number(&self) -> u32 {
+        u32::from(self.major)
+            .saturating_mul(1_000_000)
+            .saturating_add(u32::from(self.minor) * 1000 + u32::from(self.patch))
+    }
+}
+
+impl fmt::Display for SqliteVersion {
+    /// Renders the version, e.g. `3.44.2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
 /// The 4-byte big-endian integer at offset 96 stores the SQLITE_VERSION_NUMBER
 /// value for the SQLite library that most recently modified the database file. The
 /// 4-byte big-endian integer at offset 92 is the value of the change counter when
 /// the version number was stored. The integer at offset 92 indicates which
 /// transaction the version number is valid for and is sometimes called the
 /// "version-valid-for number".
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LastUpdate {
     pub sqlite_version_number: u32,
     pub version_valid_for: u32,
 }
 
-#[derive(Debug)]
+impl LastUpdate {
+    /// The version of the SQLite library that most recently modified the database.
+    pub fn version(&self) -> SqliteVersion {
+        SqliteVersion::from_number(self.sqlite_version_number)
+    }
+
+    /// Decodes the SQLITE_VERSION_NUMBER into `(major, minor, patch)`. A version
+    /// number of 0 decodes to `(0, 0, 0)`.
+    pub fn sqlite_version(&self) -> (u16, u16, u16) {
+        let v = self.version();
+        (v.major, v.minor, v.patch)
+    }
+}
+
+impl fmt::Display for LastUpdate {
+    /// Renders the SQLite version, e.g. `3.44.2`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.version())
+    }
+}
+
+/// A header field that differs between two headers, as reported by
+/// [`SQLite3Header::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderChange {
+    FileChangeCounter { old: u32, new: u32 },
+    InHeaderDatabaseSize { old: u32, new: u32 },
+    FreelistPageIndex { old: u32, new: u32 },
+    FreelistCount { old: u32, new: u32 },
+    SchemaCookie { old: u32, new: u32 },
+    UserVersion { old: u32, new: u32 },
+    VersionValidFor { old: u32, new: u32 },
+    SqliteVersionNumber { old: u32, new: u32 },
+}
+
+/// How the in-header database size compares with the actual size of the database
+/// file, as reported by [`SQLite3Header::check_size_consistency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SizeConsistency {
+    /// The in-header database size is valid and matches the file length in pages.
+    Consistent,
+    /// The in-header database size is valid but does not match the file length, as
+    /// when the file has been truncated.
+    Mismatch { in_header: u32, actual: u64 },
+    /// The file change counter does not match the version-valid-for number, so the
+    /// in-header database size was likely left behind by a legacy writer.
+    Stale,
+    /// The in-header database size is zero.
+    Unused,
+}
+
+/// A header invariant that does not hold, as reported by
+/// [`SQLite3Header::validate`] and [`SQLite3Header::from_bytes_lossy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationIssue {
+    /// The input ends before the end of the header, so the fields past its end could
+    /// not be read.
+    TooShort { expected: usize, found: usize },
+    /// The header does not begin with the magic header string.
+    InvalidMagicHeaderString([u8; 16]),
+    /// The page size, in bytes, is not a power of two between 512 and 65536.
+    InvalidPageSize(u32),
+    /// A file format version byte is zero.
+    InvalidFileFormat { offset: usize, value: u8 },
+    /// The reserved space leaves fewer than 480 usable bytes per page.
+    UsableSizeTooSmall { page_size: u32, reserved: u8 },
+    /// A payload fraction does not hold the only value it may.
+    InvalidPayloadFraction { offset: usize, expected: u8, found: u8 },
+    /// The incremental-vacuum mode is set without auto-vacuum being enabled.
+    InconsistentVacuumState(u32),
+    /// The schema format number is zero.
+    InvalidSchemaFormat(u32),
+    /// A field holds a value from a newer file format than this library models: a
+    /// file format read version above 2 or a schema format number above 4.
+    UnsupportedFormat { field: &'static str, value: u32 },
+    /// The text encoding is not 1, 2, or 3.
+    InvalidTextEncoding(u32),
+    /// The file change counter does not match the version-valid-for number, so the
+    /// in-header database size may be stale.
+    StaleInHeaderDatabaseSize {
+        file_change_counter: u32,
+        version_valid_for: u32,
+    },
+    /// A byte of the reserved expansion space at offsets 72 through 91 is not zero.
+    NonZeroReserved { offset: usize },
+}
+
+/// Checks every invariant of the header at the start of `bytes`, collecting each one
+/// that does not hold instead of stopping at the first. Fields past the end of
+/// `bytes` are not checked.
+#[cfg(feature = "alloc")]
+fn header_issues(bytes: &[u8]) -> Vec<ValidationIssue> {
+    let byte = |offset| bytes.get(offset).copied();
+    let read = |offset| u32_at(bytes, offset).ok();
+    let mut issues = Vec::new();
+
+    if bytes.len() < HEADER_SIZE {
+        issues.push(ValidationIssue::TooShort {
+            expected: HEADER_SIZE,
+            found: bytes.len(),
+        });
+    }
+
+    if let Ok(magic) = field(bytes, offsets::MAGIC_HEADER_STRING, offsets::MAGIC_HEADER_STRING_LEN) {
+        if magic != MAGIC_HEADER_BYTES {
+            let mut found = [0; offsets::MAGIC_HEADER_STRING_LEN];
+            found.copy_from_slice(magic);
+            issues.push(ValidationIssue::InvalidMagicHeaderString(found));
+        }
+    }
+
+    if let Ok(page_size) = u16_at(bytes, offsets::PAGE_SIZE) {
+        let page_size = page_size_to_bytes(page_size);
+        if !page_size.is_power_of_two() || page_size < 512 {
+            issues.push(ValidationIssue::InvalidPageSize(page_size));
+        } else if let Some(reserved) = byte(offsets::RESERVED_BYTES_PER_PAGE) {
+            if page_size - u32::from(reserved) < MINIMUM_USABLE_SIZE {
+                issues.push(ValidationIssue::UsableSizeTooSmall {
+                    page_size,
+                    reserved,
+                });
+            }
+        }
+    }
+
+    for &offset in &[offsets::FILE_FORMAT_WRITE_VERSION, offsets::FILE_FORMAT_READ_VERSION] {
+        if let Some(value) = byte(offset) {
+            match file_format_from_byte(offset, value) {
+                Err(Error::UnsupportedFormat { field, value }) => {
+                    issues.push(ValidationIssue::UnsupportedFormat { field, value })
+                }
+                Err(_) => issues.push(ValidationIssue::InvalidFileFormat { offset, value }),
+                Ok(_) => {}
+            }
+        }
+    }
+
+    for &(offset, expected) in &PAYLOAD_FRACTIONS {
+        match byte(offset) {
+            Some(found) if found != expected => {
+                issues.push(ValidationIssue::InvalidPayloadFraction {
+                    offset,
+                    expected,
+                    found,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(schema_format) = read(offsets::SCHEMA_FORMAT) {
+        match schema_format_from_u32(schema_format) {
+            Err(Error::UnsupportedFormat { field, value }) => {
+                issues.push(ValidationIssue::UnsupportedFormat { field, value })
+            }
+            Err(_) => issues.push(ValidationIssue::InvalidSchemaFormat(schema_format)),
+            Ok(_) => {}
+        }
+    }
+
+    if let Some(encoding) = read(offsets::DATABASE_TEXT_ENCODING) {
+        if text_encoding_from_u32(encoding).is_err() {
+            issues.push(ValidationIssue::InvalidTextEncoding(encoding));
+        }
+    }
+
+    if let (Some(0), Some(mode)) = (
+        read(offsets::LARGEST_ROOT_BTREE_PAGE),
+        read(offsets::INCREMENTAL_VACUUM_MODE),
+    ) {
+        if mode != 0 {
+            issues.push(ValidationIssue::InconsistentVacuumState(mode));
+        }
+    }
+
+    if let Ok(reserved) = field(bytes, offsets::RESERVED, offsets::RESERVED_LEN) {
+        for (i, _) in reserved.iter().enumerate().filter(|&(_, &b)| b != 0) {
+            issues.push(ValidationIssue::NonZeroReserved {
+                offset: offsets::RESERVED + i,
+            });
+        }
+    }
+
+    if let (Some(file_change_counter), Some(version_valid_for)) = (
+        read(offsets::FILE_CHANGE_COUNTER),
+        read(offsets::VERSION_VALID_FOR),
+    ) {
+        if file_change_counter != version_valid_for {
+            issues.push(ValidationIssue::StaleInHeaderDatabaseSize {
+                file_change_counter,
+                version_valid_for,
+            });
+        }
+    }
+    issues
+}
+
+/// The fields recovered from a damaged header by
+/// [`SQLite3Header::from_bytes_lossy`]. A field is `None` when it lies past the end
+/// of the input or holds a value that cannot be represented, such as an unknown
+/// text encoding. Integer fields hold their raw values even when they break an
+/// invariant, which is reported as a [`ValidationIssue`] instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialHeader {
+    pub magic_header_string: Option<[u8; 16]>,
+    pub page_size: Option<u16>,
+    pub file_format_write_version: Option<FileFormat>,
+    pub file_format_read_version: Option<FileFormat>,
+    pub reserved_bytes_per_page: Option<u8>,
+    pub maximum_embedded_payload_fraction: Option<u8>,
+    pub minimum_embedded_payload_fraction: Option<u8>,
+    pub leaf_payload_fraction: Option<u8>,
+    pub file_change_counter: Option<u32>,
+    pub in_header_database_size: Option<u32>,
+    pub freelist_page_index: Option<u32>,
+    pub freelist_count: Option<u32>,
+    pub schema_cookie: Option<u32>,
+    pub schema_format: Option<SchemaFormat>,
+    pub default_page_cache_size: Option<i32>,
+    pub largest_root_btree_page: Option<u32>,
+    pub database_text_encoding: Option<DatabaseTextEncoding>,
+    pub user_version: Option<u32>,
+    pub incremental_vacuum_mode: Option<u32>,
+    pub application_id: Option<u32>,
+    pub reserved_expansion: Option<[u8; 20]>,
+    pub version_valid_for: Option<u32>,
+    pub sqlite_version_number: Option<u32>,
+}
+
+/// With the `serde` feature, a deserialized header is checked exactly as a parsed one
+/// is, so deserializing fails with the [`Error`] that parsing the header's bytes
+/// would return.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "de::HeaderFields"))]
 pub struct SQLite3Header {
-    page_size: u16,
+    page_size: PageSize,
 
     file_format_write_version: FileFormat,
     file_format_read_version: FileFormat,
@@ -158,7 +1011,7 @@ pub struct SQLite3Header {
 
     schema: Schema,
 
-    default_page_cache_size: u32,
+    default_page_cache_size: i32,
 
     database_text_encoding: DatabaseTextEncoding,
 
@@ -168,54 +1021,587 @@ pub struct SQLite3Header {
 
     application_id: u32,
 
+    #[cfg_attr(feature = "serde", serde(default))]
+    reserved_expansion: [u8; 20],
+
     last_update: LastUpdate,
 }
 
 impl SQLite3Header {
-    /// All other bytes of the database file header are reserved for future expansion
-    /// and must be set to zero.
-    #[allow(non_upper_case_globals)]
-    pub const reserved: [u8; 20] = [0; 20];
+    /// Parses the first 100 bytes of `bytes` as a database header. Any bytes past the
+    /// header are ignored, so the contents of a whole database file can be passed in.
+    /// A file format read version above 2 or a schema format above 4 means the file
+    /// uses a newer file format than this library models, which is reported as
+    /// [`Error::UnsupportedFormat`] rather than as corruption.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_at(bytes, 0)
+    }
 
-    /// Every valid SQLite database file begins with the following 16 bytes (in hex):
-    /// 53 51 4c 69 74 65 20 66 6f 72 6d 61 74 20 33 00. This byte sequence corresponds
-    /// to the UTF-8 string "SQLite format 3" including the nul terminator character at
-    /// the end.
-    pub fn magic_header_string(&self) -> &str {
-        std::str::from_utf8(&MAGIC_HEADER_BYTES).unwrap()
+    /// Parses the 100 bytes of `bytes` starting at `offset` as a database header, for
+    /// databases embedded within a larger file, such as those written by the
+    /// appendvfs extension.
+    pub fn from_bytes_at(bytes: &[u8], offset: usize) -> Result<Self, Error> {
+        let bytes = match bytes.get(offset..) {
+            Some(rest) if rest.len() >= HEADER_SIZE => rest,
+            _ => {
+                return Err(Error::TooShort {
+                    expected: offset.saturating_add(HEADER_SIZE),
+                    found: bytes.len(),
+                })
+            }
+        };
+        check_length_and_magic(bytes)?;
+
+        let file_format_read_version = file_format_from_byte(
+            offsets::FILE_FORMAT_READ_VERSION,
+            bytes[offsets::FILE_FORMAT_READ_VERSION],
+        )?;
+
+        let schema_format = schema_format_from_u32(u32_at(bytes, offsets::SCHEMA_FORMAT)?)?;
+
+        let database_text_encoding =
+            text_encoding_from_u32(u32_at(bytes, offsets::DATABASE_TEXT_ENCODING)?)?;
+
+        let largest_root_btree_page = u32_at(bytes, offsets::LARGEST_ROOT_BTREE_PAGE)?;
+        let incremental_vacuum = u32_at(bytes, offsets::INCREMENTAL_VACUUM_MODE)?;
+        let vacuum = match (largest_root_btree_page, incremental_vacuum) {
+            (0, codes::VACUUM_AUTO) => None,
+            (0, _) => return Err(Error::InconsistentVacuumState(incremental_vacuum)),
+            (_, codes::VACUUM_AUTO) => Some(Vacuum {
+                largest_root_btree_page,
+                mode: VacuumMode::Auto,
+            }),
+            (_, _) => Some(Vacuum {
+                largest_root_btree_page,
+                mode: VacuumMode::Incremental,
+            }),
+        };
+
+        let page_size = PageSize::from_on_disk(u16_at(bytes, offsets::PAGE_SIZE)?)?;
+        let page_size_bytes = page_size.bytes();
+
+        let reserved_bytes_per_page = bytes[offsets::RESERVED_BYTES_PER_PAGE];
+        if page_size_bytes - u32::from(reserved_bytes_per_page) < MINIMUM_USABLE_SIZE {
+            return Err(Error::InvalidReservedSpace {
+                page_size: page_size_bytes,
+                reserved: reserved_bytes_per_page,
+            });
+        }
+
+        for &(offset, expected) in &PAYLOAD_FRACTIONS {
+            if bytes[offset] != expected {
+                return Err(Error::InvalidPayloadFraction {
+                    offset,
+                    expected,
+                    found: bytes[offset],
+                });
+            }
+        }
+
+        Ok(Self {
+            page_size,
+            file_format_write_version: file_format_from_byte(
+                offsets::FILE_FORMAT_WRITE_VERSION,
+                bytes[offsets::FILE_FORMAT_WRITE_VERSION],
+            )?,
+            file_format_read_version,
+            reserved_bytes_per_page,
+            payload_fraction: Payload {
+                maximum_embedded_fraction: bytes[offsets::MAXIMUM_EMBEDDED_PAYLOAD_FRACTION],
+                minimum_embedded_fraction: bytes[offsets::MINIMUM_EMBEDDED_PAYLOAD_FRACTION],
+                leaf_fraction: bytes[offsets::LEAF_PAYLOAD_FRACTION],
+            },
+            file_change_counter: u32_at(bytes, offsets::FILE_CHANGE_COUNTER)?,
+            in_header_database_size: u32_at(bytes, offsets::IN_HEADER_DATABASE_SIZE)?,
+            freelist: Freelist {
+                page_index: u32_at(bytes, offsets::FREELIST_PAGE_INDEX)?,
+                count: u32_at(bytes, offsets::FREELIST_COUNT)?,
+            },
+            schema: Schema {
+                cookie: u32_at(bytes, offsets::SCHEMA_COOKIE)?,
+                format: schema_format,
+            },
+            default_page_cache_size: i32_at(bytes, offsets::DEFAULT_PAGE_CACHE_SIZE)?,
+            database_text_encoding,
+            user_version: u32_at(bytes, offsets::USER_VERSION)?,
+            vacuum,
+            application_id: u32_at(bytes, offsets::APPLICATION_ID)?,
+            reserved_expansion: field(bytes, offsets::RESERVED, offsets::RESERVED_LEN)?
+                .try_into()
+                .map_err(|_| Error::TooShort {
+                    expected: offsets::RESERVED + offsets::RESERVED_LEN,
+                    found: bytes.len(),
+                })?,
+            last_update: LastUpdate {
+                version_valid_for: u32_at(bytes, offsets::VERSION_VALID_FOR)?,
+                sqlite_version_number: u32_at(bytes, offsets::SQLITE_VERSION_NUMBER)?,
+            },
+        })
     }
 
-    /// The two-byte value beginning at offset 16 determines the page size of the
-    /// database. For SQLite versions 3.7.0.1 (2010-08-04) and earlier, this value is
-    /// interpreted as a big-endian integer and must be a power of two between 512 and
-    /// 32768, inclusive. Beginning with SQLite version 3.7.1 (2010-08-23), a page size
-    /// of 65536 bytes is supported. The value 65536 will not fit in a two-byte
-    /// integer, so to specify a 65536-byte page size, the value at offset 16 is 0x00
-    /// 0x01. This value can be interpreted as a big-endian 1 and thought of as a magic
-    /// number to represent the 65536 page size. Or one can view the two-byte field as
-    /// a little endian number and say that it represents the page size divided by 256.
-    /// These two interpretations of the page-size field are equivalent.
-    pub fn page_size(&self) -> u16 {
-        self.page_size
+    /// Recovers whatever fields can be decoded from a damaged or truncated header,
+    /// never failing. Every problem found is reported in the returned issues, like
+    /// [`SQLite3Header::validate`] does for a parsed header.
+    #[cfg(feature = "alloc")]
+    pub fn from_bytes_lossy(bytes: &[u8]) -> (PartialHeader, Vec<ValidationIssue>) {
+        let byte = |offset| bytes.get(offset).copied();
+        let read = |offset| u32_at(bytes, offset).ok();
+        let header = PartialHeader {
+            magic_header_string: field(
+                bytes,
+                offsets::MAGIC_HEADER_STRING,
+                offsets::MAGIC_HEADER_STRING_LEN,
+            )
+            .ok()
+            .and_then(|magic| magic.try_into().ok()),
+            page_size: u16_at(bytes, offsets::PAGE_SIZE).ok(),
+            file_format_write_version: byte(offsets::FILE_FORMAT_WRITE_VERSION)
+                .and_then(|v| file_format_from_byte(offsets::FILE_FORMAT_WRITE_VERSION, v).ok()),
+            file_format_read_version: byte(offsets::FILE_FORMAT_READ_VERSION)
+                .and_then(|v| file_format_from_byte(offsets::FILE_FORMAT_READ_VERSION, v).ok()),
+            reserved_bytes_per_page: byte(offsets::RESERVED_BYTES_PER_PAGE),
+            maximum_embedded_payload_fraction: byte(offsets::MAXIMUM_EMBEDDED_PAYLOAD_FRACTION),
+            minimum_embedded_payload_fraction: byte(offsets::MINIMUM_EMBEDDED_PAYLOAD_FRACTION),
+            leaf_payload_fraction: byte(offsets::LEAF_PAYLOAD_FRACTION),
+            file_change_counter: read(offsets::FILE_CHANGE_COUNTER),
+            in_header_database_size: read(offsets::IN_HEADER_DATABASE_SIZE),
+            freelist_page_index: read(offsets::FREELIST_PAGE_INDEX),
+            freelist_count: read(offsets::FREELIST_COUNT),
+            schema_cookie: read(offsets::SCHEMA_COOKIE),
+            schema_format: read(offsets::SCHEMA_FORMAT)
+                .and_then(|v| schema_format_from_u32(v).ok()),
+            default_page_cache_size: i32_at(bytes, offsets::DEFAULT_PAGE_CACHE_SIZE).ok(),
+            largest_root_btree_page: read(offsets::LARGEST_ROOT_BTREE_PAGE),
+            database_text_encoding: read(offsets::DATABASE_TEXT_ENCODING)
+                .and_then(|v| text_encoding_from_u32(v).ok()),
+            user_version: read(offsets::USER_VERSION),
+            incremental_vacuum_mode: read(offsets::INCREMENTAL_VACUUM_MODE),
+            application_id: read(offsets::APPLICATION_ID),
+            reserved_expansion: field(bytes, offsets::RESERVED, offsets::RESERVED_LEN)
+                .ok()
+                .and_then(|reserved| reserved.try_into().ok()),
+            version_valid_for: read(offsets::VERSION_VALID_FOR),
+            sqlite_version_number: read(offsets::SQLITE_VERSION_NUMBER),
+        };
+        (header, header_issues(bytes))
     }
 
-    pub fn file_format_read_version(&self) -> &FileFormat {
-        &self.file_format_read_version
+    /// Parses a header like [`SQLite3Header::from_bytes`], additionally requiring the
+    /// reserved expansion bytes at offsets 72 through 91 to be zero. Some tooling
+    /// stores data there illegitimately, which `from_bytes` tolerates.
+    pub fn from_bytes_strict(bytes: &[u8]) -> Result<Self, Error> {
+        let header = Self::from_bytes(bytes)?;
+        let reserved = field(bytes, offsets::RESERVED, offsets::RESERVED_LEN)?;
+        if let Some(i) = reserved.iter().position(|&b| b != 0) {
+            return Err(Error::NonZeroReserved {
+                offset: offsets::RESERVED + i,
+            });
+        }
+        Ok(header)
     }
 
-    pub fn file_format_write_version(&self) -> &FileFormat {
-        &self.file_format_write_version
+    /// Parses a header like [`SQLite3Header::from_bytes`], also returning a copy of the
+    /// exact 100 bytes it was parsed from.
+    pub fn from_bytes_with_raw(bytes: &[u8]) -> Result<(Self, [u8; HEADER_SIZE]), Error> {
+        let header = Self::from_bytes(bytes)?;
+        let mut raw = [0; HEADER_SIZE];
+        raw.copy_from_slice(&bytes[..HEADER_SIZE]);
+        Ok((header, raw))
     }
 
-    /// SQLite has the ability to set aside a small number of extra bytes at the end of
-    /// every page for use by extensions. These extra bytes are used, for example, by
-    /// the SQLite Encryption Extension to store a nonce and/or cryptographic checksum
-    /// associated with each page. The "reserved space" size in the 1-byte integer at
-    /// offset 20 is the number of bytes of space at the end of each page to reserve
-    /// for extensions. This value is usually 0. The value can be odd.
+    /// Reads exactly the 100 header bytes from `reader` and parses them. Nothing past
+    /// the header is read, so this can be used on sockets or large files without
+    /// buffering the whole database.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut buffer = [0; HEADER_SIZE];
+        let mut found = 0;
+        while found < HEADER_SIZE {
+            match reader.read(&mut buffer[found..]) {
+                Ok(0) => {
+                    return Err(Error::UnexpectedEof {
+                        expected: HEADER_SIZE,
+                        found,
+                    })
+                }
+                Ok(n) => found += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::Io(e)),
+            }
+        }
+        Self::from_bytes(&buffer)
+    }
 
-    /// The "usable size" of a database page is the page size specified by the 2-byte
-    /// integer at offset 16 in the header less the "reserved" space size recorded in
+    /// Reads exactly the 100 header bytes from the asynchronous `reader` and parses
+    /// them like [`SQLite3Header::from_reader`].
+    #[cfg(feature = "tokio")]
+    pub async fn from_async_reader<R: tokio::io::AsyncRead + Unpin>(
+        reader: &mut R,
+    ) -> Result<Self, Error> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buffer = [0; HEADER_SIZE];
+        let mut found = 0;
+        while found < HEADER_SIZE {
+            match reader.read(&mut buffer[found..]).await {
+                Ok(0) => {
+                    return Err(Error::UnexpectedEof {
+                        expected: HEADER_SIZE,
+                        found,
+                    })
+                }
+                Ok(n) => found += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::Io(e)),
+            }
+        }
+        Self::from_bytes(&buffer)
+    }
+
+    /// Opens the database file at `path` and parses its header. Only the start of the
+    /// file is read.
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Edits the header of the database file at `path` in place: the header is read
+    /// and parsed, passed to `f` to be modified, and written back over the first 100
+    /// bytes of the file. Only the bytes of the fields that `f` changed are updated;
+    /// every other byte, including values that [`SQLite3Header::to_bytes`] would
+    /// normalize, is written back as it was read. No other bytes of the file are
+    /// written.
+    #[cfg(feature = "std")]
+    pub fn patch_file<P: AsRef<Path>>(path: P, f: impl FnOnce(&mut Self)) -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let mut bytes = [0; HEADER_SIZE];
+        file.read_exact(&mut bytes)?;
+        let mut header = Self::from_bytes(&bytes)?;
+        let before = header.to_bytes();
+        f(&mut header);
+
+        let after = header.to_bytes();
+        for ((byte, old), new) in bytes.iter_mut().zip(&before).zip(&after) {
+            if old != new {
+                *byte = *new;
+            }
+        }
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Memory-maps the database file at `path` and parses its header, returning the
+    /// mapping alongside it so that pages can then be read without copying the file
+    /// into memory.
+    ///
+    /// # Safety caveats
+    ///
+    /// The mapping reflects the file as it changes. If another process writes to or
+    /// truncates the database while it is mapped, the returned header may no longer
+    /// describe the mapped contents, and accessing truncated parts of the mapping may
+    /// crash the process. Only map databases that no other process is modifying.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> Result<(Self, memmap2::Mmap), Error> {
+        let file = File::open(path)?;
+        // SAFETY: The caveats of mapping a file that may be modified concurrently are
+        // documented above and left to the caller.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }?;
+        let header = Self::from_bytes(&mmap)?;
+        Ok((header, mmap))
+    }
+
+    /// Parses the header at the start of a [`bytes::Bytes`] buffer without copying it.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes) -> Result<Self, Error> {
+        Self::from_bytes(buf)
+    }
+
+    /// Serializes the header back into the 100-byte on-disk layout. The reserved
+    /// expansion bytes at offsets 72 through 91 are written as they were parsed, and
+    /// are zero for a header created by this library.
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        let mut put = |offset: usize, value: &[u8]| {
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+        };
+
+        put(offsets::MAGIC_HEADER_STRING, &MAGIC_HEADER_BYTES);
+        put(offsets::PAGE_SIZE, &self.page_size.on_disk().to_be_bytes());
+        put(
+            offsets::FILE_FORMAT_WRITE_VERSION,
+            &[file_format_to_byte(&self.file_format_write_version)],
+        );
+        put(
+            offsets::FILE_FORMAT_READ_VERSION,
+            &[file_format_to_byte(&self.file_format_read_version)],
+        );
+        put(offsets::RESERVED_BYTES_PER_PAGE, &[self.reserved_bytes_per_page]);
+        put(
+            offsets::MAXIMUM_EMBEDDED_PAYLOAD_FRACTION,
+            &[self.payload_fraction.maximum_embedded_fraction],
+        );
+        put(
+            offsets::MINIMUM_EMBEDDED_PAYLOAD_FRACTION,
+            &[self.payload_fraction.minimum_embedded_fraction],
+        );
+        put(offsets::LEAF_PAYLOAD_FRACTION, &[self.payload_fraction.leaf_fraction]);
+        put(offsets::FILE_CHANGE_COUNTER, &self.file_change_counter.to_be_bytes());
+        put(offsets::IN_HEADER_DATABASE_SIZE, &self.in_header_database_size.to_be_bytes());
+        put(offsets::FREELIST_PAGE_INDEX, &self.freelist.page_index.to_be_bytes());
+        put(offsets::FREELIST_COUNT, &self.freelist.count.to_be_bytes());
+        put(offsets::SCHEMA_COOKIE, &self.schema.cookie.to_be_bytes());
+
+        put(offsets::SCHEMA_FORMAT, &u32::from(self.schema.format.number()).to_be_bytes());
+        put(offsets::DEFAULT_PAGE_CACHE_SIZE, &self.default_page_cache_size.to_be_bytes());
+
+        if let Some(vacuum) = &self.vacuum {
+            put(
+                offsets::LARGEST_ROOT_BTREE_PAGE,
+                &vacuum.largest_root_btree_page.to_be_bytes(),
+            );
+            if let VacuumMode::Incremental = vacuum.mode {
+                put(
+                    offsets::INCREMENTAL_VACUUM_MODE,
+                    &codes::VACUUM_INCREMENTAL.to_be_bytes(),
+                );
+            }
+        }
+
+        let database_text_encoding: u32 = match self.database_text_encoding {
+            DatabaseTextEncoding::Utf8 => codes::TEXT_UTF8,
+            DatabaseTextEncoding::Utf16le => codes::TEXT_UTF16LE,
+            DatabaseTextEncoding::Utf16be => codes::TEXT_UTF16BE,
+        };
+        put(offsets::DATABASE_TEXT_ENCODING, &database_text_encoding.to_be_bytes());
+        put(offsets::USER_VERSION, &self.user_version.to_be_bytes());
+        put(offsets::APPLICATION_ID, &self.application_id.to_be_bytes());
+        put(offsets::RESERVED, &self.reserved_expansion);
+        put(
+            offsets::VERSION_VALID_FOR,
+            &self.last_update.version_valid_for.to_be_bytes(),
+        );
+        put(
+            offsets::SQLITE_VERSION_NUMBER,
+            &self.last_update.sqlite_version_number.to_be_bytes(),
+        );
+
+        bytes
+    }
+
+    /// The offset within page 1 of the b-tree page header of the sqlite_schema root
+    /// page, which follows the 100-byte database header.
+    pub fn schema_root_offset(&self) -> usize {
+        HEADER_SIZE
+    }
+
+    /// The byte offset within the database file of the b-tree content of page
+    /// `page_no`: the start of the page, or for page 1 the end of the database header.
+    /// Pages are numbered beginning with 1, so `None` is returned for page 0.
+    pub fn page_content_offset(&self, page_no: u32) -> Option<u64> {
+        let start = u64::from(page_no.checked_sub(1)?) * u64::from(self.page_size_bytes());
+        Some(if page_no == 1 {
+            start + HEADER_SIZE as u64
+        } else {
+            start
+        })
+    }
+
+    /// The number of the lock-byte page, the page containing the byte at offset 2^30
+    /// (1 GiB), if the in-header database size is valid and the database extends that
+    /// far. SQLite uses the lock-byte page for file locking and never stores data in
+    /// it, so [`SQLite3Header::pages`] skips it and no ptrmap page is placed on it. For
+    /// databases whose in-header size is not valid, use
+    /// [`SQLite3Header::lock_byte_page_in_file`] with the file length.
+    pub fn lock_byte_page(&self) -> Option<u32> {
+        let page_no = self.lock_byte_page_number();
+        match self.database_size_pages() {
+            Some(pages) if pages >= page_no => Some(page_no),
+            _ => None,
+        }
+    }
+
+    /// Like [`SQLite3Header::lock_byte_page`], but for a database file of `file_len`
+    /// bytes: the database size is taken from the header when it is valid, and
+    /// otherwise from `file_len`, as by [`SQLite3Header::database_size_or_filesize`].
+    pub fn lock_byte_page_in_file(&self, file_len: u64) -> Option<u32> {
+        let page_no = self.lock_byte_page_number();
+        if self.database_size_or_filesize(file_len) >= u64::from(page_no) {
+            Some(page_no)
+        } else {
+            None
+        }
+    }
+
+    /// The number that the lock-byte page has, or would have, given the page size,
+    /// whether or not the database is large enough to contain it.
+    pub fn lock_byte_page_number(&self) -> u32 {
+        PENDING_BYTE / self.page_size_bytes() + 1
+    }
+
+    /// The page number of the ptrmap page that holds the entry for page `page_no` of
+    /// an auto-vacuum database, as computed by [`vacuum::ptrmap_page_for`] for the
+    /// usable size, except that a ptrmap page that would fall on the lock-byte page is
+    /// placed on the page after it, as SQLite does.
+    pub fn ptrmap_page_for(&self, page_no: u32) -> u32 {
+        match vacuum::ptrmap_page_for(page_no, self.usable_size()) {
+            page if page == self.lock_byte_page_number() => page + 1,
+            page => page,
+        }
+    }
+
+    /// Whether page `page_no` is a ptrmap page of an auto-vacuum database, accounting
+    /// for the lock-byte page like [`SQLite3Header::ptrmap_page_for`].
+    pub fn is_ptrmap_page(&self, page_no: u32) -> bool {
+        page_no >= 2 && self.ptrmap_page_for(page_no) == page_no
+    }
+
+    /// Reads page `page_no` of the database from `reader`, which should be positioned
+    /// over the whole database file. Pages are numbered beginning with 1, so page 0,
+    /// like a page past the end of the file, is out of bounds.
+    /// The lock-byte page can be read like any other, though it holds no data.
+    #[cfg(feature = "std")]
+    pub fn read_page<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        page_no: u32,
+    ) -> Result<Vec<u8>, Error> {
+        if page_no == 0 {
+            return Err(Error::PageOutOfBounds(page_no));
+        }
+        let page_size = self.page_size_bytes();
+        let offset = u64::from(page_no - 1) * u64::from(page_size);
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let mut page = vec![0; page_size as usize];
+        reader.read_exact(&mut page).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => Error::PageOutOfBounds(page_no),
+            _ => Error::Io(e),
+        })?;
+        Ok(page)
+    }
+
+    /// Reads every page of a database file of `file_len` bytes from `reader` in order,
+    /// yielding each with its page number. The lock-byte page is skipped as it never
+    /// holds data; see [`SQLite3Header::lock_byte_page`].
+    #[cfg(feature = "std")]
+    pub fn pages<'a, R: Read + Seek>(
+        &'a self,
+        reader: &'a mut R,
+        file_len: u64,
+    ) -> impl Iterator<Item = Result<(u32, Vec<u8>), Error>> + 'a {
+        let page_count = self.page_count(file_len).min(u64::from(u32::MAX)) as u32;
+        let lock_byte_page = self.lock_byte_page_number();
+        (1..=page_count)
+            .filter(move |&page_no| page_no != lock_byte_page)
+            .map(move |page_no| self.read_page(reader, page_no).map(|page| (page_no, page)))
+    }
+
+    /// Writes the 100-byte on-disk layout of the header, as returned by
+    /// [`SQLite3Header::to_bytes`], to `writer`.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// All other bytes of the database file header are reserved for future expansion
+    /// and must be set to zero.
+    #[allow(non_upper_case_globals)]
+    pub const reserved: [u8; 20] = [0; 20];
+
+    /// The reserved expansion bytes at offsets 72 through 91 as parsed. They should be
+    /// zero, but some third-party tools store data there, which parsing tolerates
+    /// unless [`SQLite3Header::from_bytes_strict`] is used.
+    pub fn reserved_expansion(&self) -> &[u8; 20] {
+        &self.reserved_expansion
+    }
+
+    /// Whether every reserved expansion byte is zero, as the file format requires.
+    pub fn reserved_expansion_is_zero(&self) -> bool {
+        self.reserved_expansion.iter().all(|&b| b == 0)
+    }
+
+    /// Every valid SQLite database file begins with the following 16 bytes (in hex):
+    /// 53 51 4c 69 74 65 20 66 6f 72 6d 61 74 20 33 00. This byte sequence corresponds
+    /// to the UTF-8 string "SQLite format 3" including the nul terminator character at
+    /// the end.
+    pub const MAGIC: &'static str = "SQLite format 3\0";
+
+    /// The magic header string, [`SQLite3Header::MAGIC`], which is the same for every
+    /// header.
+    pub fn magic_header_string(&self) -> &str {
+        Self::MAGIC
+    }
+
+    /// The two-byte value beginning at offset 16 determines the page size of the
+    /// database. For SQLite versions 3.7.0.1 (2010-08-04) and earlier, this value is
+    /// interpreted as a big-endian integer and must be a power of two between 512 and
+    /// 32768, inclusive. Beginning with SQLite version 3.7.1 (2010-08-23), a page size
+    /// of 65536 bytes is supported. The value 65536 will not fit in a two-byte
+    /// integer, so to specify a 65536-byte page size, the value at offset 16 is 0x00
+    /// 0x01. This value can be interpreted as a big-endian 1 and thought of as a magic
+    /// number to represent the 65536 page size. Or one can view the two-byte field as
+    /// a little endian number and say that it represents the page size divided by 256.
+    /// These two interpretations of the page-size field are equivalent.
+    ///
+    /// See [`PageSize::bytes`] and [`PageSize::on_disk`] for the two
+    /// interpretations.
+    pub fn page_size(&self) -> PageSize {
+        self.page_size
+    }
+
+    /// The page size in bytes, resolving the on-disk value 1 to 65536.
+    pub fn page_size_bytes(&self) -> u32 {
+        self.page_size.bytes()
+    }
+
+    pub fn file_format_read_version(&self) -> &FileFormat {
+        &self.file_format_read_version
+    }
+
+    pub fn file_format_write_version(&self) -> &FileFormat {
+        &self.file_format_write_version
+    }
+
+    /// A database whose write version is greater than 2 but whose read version is 1
+    /// or 2 must be treated as read-only. A read version greater than 2 is rejected
+    /// by parsing, so this only depends on the write version.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.file_format_write_version, FileFormat::Inaccessible)
+    }
+
+    /// Whether the database may be written, that is, whether both the read and write
+    /// versions are 1 or 2. A parsed header's read version is always 1 or 2, so this
+    /// is the same as `!is_read_only()`.
+    pub fn is_accessible(&self) -> bool {
+        !self.is_read_only()
+    }
+
+    /// The journal mode implied by the file format versions, or `None` if the read
+    /// and write versions disagree or either is inaccessible.
+    pub fn journal_mode(&self) -> Option<JournalMode> {
+        match (self.file_format_read_version, self.file_format_write_version) {
+            (FileFormat::Legacy, FileFormat::Legacy) => Some(JournalMode::Rollback),
+            (FileFormat::WriteAheadLogging, FileFormat::WriteAheadLogging) => {
+                Some(JournalMode::Wal)
+            }
+            _ => None,
+        }
+    }
+
+    /// SQLite has the ability to set aside a small number of extra bytes at the end of
+    /// every page for use by extensions. These extra bytes are used, for example, by
+    /// the SQLite Encryption Extension to store a nonce and/or cryptographic checksum
+    /// associated with each page. The "reserved space" size in the 1-byte integer at
+    /// offset 20 is the number of bytes of space at the end of each page to reserve
+    /// for extensions. This value is usually 0. The value can be odd.
+    ///
+    /// The "usable size" of a database page is the page size specified by the 2-byte
+    /// integer at offset 16 in the header less the "reserved" space size recorded in
     /// the 1-byte integer at offset 20 in the header. The usable size of a page might
     /// be an odd number. However, the usable size is not allowed to be less than 480.
     /// In other words, if the page size is 512, then the reserved space size cannot
@@ -224,6 +1610,41 @@ impl SQLite3Header {
         self.reserved_bytes_per_page
     }
 
+    /// The usable size of a page in bytes: the page size less the reserved space.
+    /// Cell content offsets within b-tree pages are relative to this size.
+    pub fn usable_size(&self) -> u32 {
+        self.page_size_bytes() - u32::from(self.reserved_bytes_per_page)
+    }
+
+    /// Whether the database is likely encrypted by the SQLite Encryption Extension,
+    /// judging by its reserved space. This holds only when exactly 16 bytes are
+    /// reserved per page, the space SEE's AES modes use for each page's nonce.
+    ///
+    /// This is only a heuristic, and a conservative one. A database with an encrypted
+    /// header does not parse at all, so only encryption schemes that leave the header
+    /// in plaintext can be detected. Other reserved sizes return `false` even where an
+    /// encryption extension uses them, such as SQLCipher's 48 and 80 bytes, because
+    /// they are as likely to be reserved for other purposes. Conversely, any
+    /// application may reserve 16 bytes, so a `true` result should be confirmed by
+    /// checking that the page content does not decode.
+    pub fn likely_encrypted(&self) -> bool {
+        self.reserved_bytes_per_page == SEE_RESERVED_BYTES
+    }
+
+    /// The largest cell payload that is stored entirely on a b-tree page of type
+    /// `page_type`, without spilling onto overflow pages. This is the usable size
+    /// less 35 for table b-tree leaf pages and ((U-12)*64/255)-23 for index pages,
+    /// where U is the usable size.
+    pub fn max_local_payload(&self, page_type: btree::PageType) -> u32 {
+        btree::max_local_payload(&page_type, self.usable_size())
+    }
+
+    /// The number of bytes of an overflowing cell payload that are always stored on
+    /// the b-tree page: ((U-12)*32/255)-23, where U is the usable size.
+    pub fn min_local_payload(&self) -> u32 {
+        btree::min_local_payload(self.usable_size())
+    }
+
     pub fn payload_fraction(&self) -> &Payload {
         &self.payload_fraction
     }
@@ -235,7 +1656,7 @@ impl SQLite3Header {
     /// A process will normally want to flush its database page cache when another
     /// process modified the database, since the cache has become stale. The file
     /// change counter facilitates this.
-
+    ///
     /// In WAL mode, changes to the database are detected using the wal-index and so
     /// the change counter is not needed. Hence, the change counter might not be
     /// incremented on each transaction in WAL mode.
@@ -243,6 +1664,20 @@ impl SQLite3Header {
         self.file_change_counter
     }
 
+    /// Increments the file change counter, wrapping from `u32::MAX` to 0, so that
+    /// other readers notice a rewritten database. Unless the version-valid-for number
+    /// at offset 92 is updated to match with [`SQLite3Header::set_version_valid_for`],
+    /// the in-header database size will no longer be considered valid.
+    pub fn bump_change_counter(&mut self) {
+        self.file_change_counter = self.file_change_counter.wrapping_add(1);
+    }
+
+    /// Sets the version-valid-for number at offset 92. Setting it to the file change
+    /// counter marks the in-header database size as valid.
+    pub fn set_version_valid_for(&mut self, v: u32) {
+        self.last_update.version_valid_for = v;
+    }
+
     /// The 4-byte big-endian integer at offset 28 into the header stores the size of
     /// the database file in pages. If this in-header datasize size is not valid (see
     /// the next paragraph), then the database size is computed by looking at the
@@ -250,7 +1685,7 @@ impl SQLite3Header {
     /// in-header database size and used the actual file size exclusively. Newer
     /// versions of SQLite use the in-header database size if it is available but fall
     /// back to the actual file size if the in-header database size is not valid.
-
+    ///
     /// The in-header database size is only considered to be valid if it is non-zero
     /// and if the 4-byte change counter at offset 24 exactly matches the 4-byte
     /// version-valid-for number at offset 92. The in-header database size is always
@@ -266,6 +1701,82 @@ impl SQLite3Header {
         self.in_header_database_size
     }
 
+    /// Whether the in-header database size can be trusted: it must be non-zero and the
+    /// file change counter must match the version-valid-for number.
+    pub fn in_header_database_size_valid(&self) -> bool {
+        self.in_header_database_size != 0
+            && self.file_change_counter == self.last_update.version_valid_for
+    }
+
+    /// Whether the in-header database size was written by a SQLite version that
+    /// maintains it, 3.7.0 or later, in the same transaction that last changed the
+    /// database, as shown by the file change counter matching the version-valid-for
+    /// number. A zero size is never trusted, as it means the size is unused.
+    pub fn trusts_in_header_size(&self) -> bool {
+        let maintained_since = SqliteVersion {
+            major: 3,
+            minor: 7,
+            patch: 0,
+        };
+        self.last_update.version() >= maintained_since
+            && self.file_change_counter == self.last_update.version_valid_for
+            && self.in_header_database_size != 0
+    }
+
+    /// The database size in pages, if the in-header database size is valid. When this
+    /// is `None` the size must be computed from the actual size of the database file.
+    pub fn database_size_pages(&self) -> Option<u32> {
+        if self.in_header_database_size_valid() {
+            Some(self.in_header_database_size)
+        } else {
+            None
+        }
+    }
+
+    /// Compares the in-header database size with `file_len`, the actual size of the
+    /// database file in bytes.
+    pub fn check_size_consistency(&self, file_len: u64) -> SizeConsistency {
+        if self.in_header_database_size == 0 {
+            return SizeConsistency::Unused;
+        }
+        if !self.in_header_database_size_valid() {
+            return SizeConsistency::Stale;
+        }
+        let actual = self.page_count(file_len);
+        if actual == u64::from(self.in_header_database_size) {
+            SizeConsistency::Consistent
+        } else {
+            SizeConsistency::Mismatch {
+                in_header: self.in_header_database_size,
+                actual,
+            }
+        }
+    }
+
+    /// The number of whole pages in a database file of `file_len` bytes.
+    pub fn page_count(&self, file_len: u64) -> u64 {
+        file_len / u64::from(self.page_size_bytes())
+    }
+
+    /// The fraction of the pages of a database file of `file_len` bytes that are on
+    /// the freelist, between 0 and 1, or `None` if the file holds no whole pages.
+    pub fn free_page_ratio(&self, file_len: u64) -> Option<f64> {
+        match self.page_count(file_len) {
+            0 => None,
+            pages => Some((f64::from(self.freelist.count) / pages as f64).min(1.0)),
+        }
+    }
+
+    /// The database size in pages, using the in-header database size when it is valid
+    /// and otherwise computing it from `file_len`, the actual size of the database
+    /// file in bytes, as SQLite does.
+    pub fn database_size_or_filesize(&self, file_len: u64) -> u64 {
+        match self.database_size_pages() {
+            Some(pages) => u64::from(pages),
+            None => self.page_count(file_len),
+        }
+    }
+
     pub fn freelist(&self) -> &Freelist {
         &self.freelist
     }
@@ -274,15 +1785,32 @@ impl SQLite3Header {
         &self.schema
     }
 
+    /// Whether the schema cookie differs from `previous_cookie`, a value read from an
+    /// earlier version of the header, meaning the schema has changed since and any
+    /// cached copy of sqlite_schema must be read again. The cookie is compared for
+    /// equality only, since it wraps around.
+    pub fn schema_changed_since(&self, previous_cookie: u32) -> bool {
+        self.schema.cookie != previous_cookie
+    }
+
     /// The 4-byte big-endian signed integer at offset 48 is the suggested cache size
     /// in pages for the database file. The value is a suggestion only and SQLite is
     /// under no obligation to honor it. The absolute value of the integer is used as
     /// the suggested size. The suggested cache size can be set using the
     /// default_cache_size pragma.
-    pub fn default_page_cache_size(&self) -> u32 {
+    ///
+    /// This returns the raw signed value; see
+    /// [`SQLite3Header::suggested_cache_size_pages`] for the suggested size.
+    pub fn default_page_cache_size(&self) -> i32 {
         self.default_page_cache_size
     }
 
+    /// The suggested cache size in pages, which is the absolute value of the integer
+    /// at offset 48.
+    pub fn suggested_cache_size_pages(&self) -> u32 {
+        self.default_page_cache_size.unsigned_abs()
+    }
+
     pub fn database_text_encoding(&self) -> &DatabaseTextEncoding {
         &self.database_text_encoding
     }
@@ -293,12 +1821,31 @@ impl SQLite3Header {
         self.user_version
     }
 
+    /// Sets the user version. SQLite itself ignores this field, so it can be rewritten
+    /// with [`SQLite3Header::to_bytes`] without touching the rest of the database.
+    pub fn set_user_version(&mut self, v: u32) {
+        self.user_version = v;
+    }
+
     pub fn vacuum(&self) -> Option<&Vacuum> {
-        if let Some(vac) = &self.vacuum {
-            Some(vac)
-        } else {
-            None
-        }
+        self.vacuum.as_ref()
+    }
+
+    /// Whether auto-vacuum is enabled, in either auto_vacuum or incremental_vacuum
+    /// mode. The database file contains ptrmap pages exactly when this is true.
+    pub fn is_auto_vacuum(&self) -> bool {
+        self.vacuum.is_some()
+    }
+
+    /// Whether auto-vacuum is enabled in incremental_vacuum mode.
+    pub fn is_incremental_vacuum(&self) -> bool {
+        matches!(
+            self.vacuum,
+            Some(Vacuum {
+                mode: VacuumMode::Incremental,
+                ..
+            })
+        )
     }
 
     /// The 4-byte big-endian integer at offset 68 is an "Application ID" that can be
@@ -312,7 +1859,1748 @@ impl SQLite3Header {
         self.application_id
     }
 
+    /// Sets the application ID. SQLite itself ignores this field, so it can be
+    /// rewritten with [`SQLite3Header::to_bytes`] without touching the rest of the
+    /// database.
+    pub fn set_application_id(&mut self, id: u32) {
+        self.application_id = id;
+    }
+
+    /// The name of the file format identified by the application ID, if it is one of
+    /// the IDs registered in magic.txt.
+    pub fn application_id_name(&self) -> Option<&'static str> {
+        APPLICATION_IDS
+            .iter()
+            .find(|&&(id, _)| id == self.application_id)
+            .map(|&(_, name)| name)
+    }
+
+    /// Whether the database was last written by a SQLite release newer than
+    /// [`SUPPORTED_SQLITE_VERSION`], which may use file format features that this
+    /// library does not model.
+    pub fn written_by_newer_sqlite(&self) -> bool {
+        self.last_update.version() > SUPPORTED_SQLITE_VERSION
+    }
+
+    /// The GeoPackage version as (major, minor, patch), if the application ID is one
+    /// used by GeoPackage. Versions 1.0 and 1.1 are identified by the application IDs
+    /// "GP10" and "GP11" alone. Later versions use the application ID "GPKG" and store
+    /// the version in the user version as major * 10000 + minor * 100 + patch, e.g.
+    /// 10200 for 1.2.0; `None` is returned if it is not a version of at least 1.2.
+    pub fn geopackage_version(&self) -> Option<(u8, u8, u8)> {
+        match self.application_id {
+            0x47503130 => Some((1, 0, 0)),
+            0x47503131 => Some((1, 1, 0)),
+            0x47504b47 if (10200..1_000_000).contains(&self.user_version) => {
+                let v = self.user_version;
+                Some(((v / 10000) as u8, (v / 100 % 100) as u8, (v % 100) as u8))
+            }
+            _ => None,
+        }
+    }
+
+    /// A one-line summary of the header in the style of file(1), e.g. `SQLite 3.x
+    /// database, page size 4096, UTF-8, WAL, last written by 3.44.2, application id
+    /// GeoPackage`.
+    #[cfg(feature = "alloc")]
+    pub fn describe(&self) -> String {
+        let mut description = format!(
+            "SQLite 3.x database, page size {}, {}, {}, last written by {}",
+            self.page_size_bytes(),
+            self.database_text_encoding,
+            self.journal_mode_name(),
+            self.last_update,
+        );
+        match (self.application_id, self.application_id_name()) {
+            (0, _) => {}
+            (_, Some(name)) => description.push_str(&format!(", application id {}", name)),
+            (id, None) => description.push_str(&format!(", application id {:#010x}", id)),
+        }
+        description
+    }
+
     pub fn last_update(&self) -> &LastUpdate {
         &self.last_update
     }
+
+    /// Checks every header invariant and reports each one that does not hold, rather
+    /// than failing at the first like parsing does. Parsing already enforces most
+    /// invariants, so this mostly reports a stale in-header database size and
+    /// non-zero reserved expansion bytes, but it also catches headers left
+    /// inconsistent by the setters. Use
+    /// [`SQLite3Header::from_bytes_lossy`] to check the bytes of a header that fails
+    /// to parse.
+    #[cfg(feature = "alloc")]
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        header_issues(&self.to_bytes())
+    }
+
+    /// A 64-bit FNV-1a hash of the fields that identify a database rather than its
+    /// current state: the page size, schema cookie, text encoding, application ID, and
+    /// SQLITE_VERSION_NUMBER. The file change counter and other fields that change on
+    /// every write are deliberately excluded. Unlike the `Hash` impl, the result is
+    /// stable across builds and platforms, so it can be stored.
+    pub fn fingerprint(&self) -> u64 {
+        let bytes = self.to_bytes();
+        [
+            (offsets::PAGE_SIZE, offsets::PAGE_SIZE_LEN),
+            (offsets::SCHEMA_COOKIE, offsets::SCHEMA_COOKIE_LEN),
+            (offsets::DATABASE_TEXT_ENCODING, offsets::DATABASE_TEXT_ENCODING_LEN),
+            (offsets::APPLICATION_ID, offsets::APPLICATION_ID_LEN),
+            (offsets::SQLITE_VERSION_NUMBER, offsets::SQLITE_VERSION_NUMBER_LEN),
+        ]
+        .iter()
+        .flat_map(|&(offset, len)| &bytes[offset..offset + len])
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    /// Lists every header field as a (name, value) pair in header order, with the
+    /// same names and values as the `Display` report, for rendering in a table.
+    /// Values are decoded: the page size is in bytes, and rows for the journal mode and
+    /// the SQLite version string follow the fields they are derived from.
+    #[cfg(feature = "alloc")]
+    pub fn report_rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = Vec::new();
+        self.visit_report_rows(|name, value| {
+            rows.push((name, value.to_string()));
+            Ok::<_, core::convert::Infallible>(())
+        })
+        .ok();
+        rows
+    }
+
+    /// The journal mode as named in the report and by [`SQLite3Header::describe`].
+    fn journal_mode_name(&self) -> &'static str {
+        match self.journal_mode() {
+            Some(JournalMode::Rollback) => "rollback",
+            Some(JournalMode::Wal) => "WAL",
+            None => "unknown",
+        }
+    }
+
+    /// Calls `row` with the name and value of each row of the report, in order,
+    /// stopping at the first error. This is the single list of rows behind both
+    /// [`SQLite3Header::report_rows`] and the `Display` report.
+    fn visit_report_rows<E>(
+        &self,
+        mut row: impl FnMut(&'static str, &dyn fmt::Display) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let journal_mode = self.journal_mode_name();
+        let (largest_root_btree_page, incremental_vacuum) = match &self.vacuum {
+            Some(vacuum) => (
+                vacuum.largest_root_btree_page,
+                matches!(vacuum.mode, VacuumMode::Incremental),
+            ),
+            None => (0, false),
+        };
+
+        row(
+            "MAGIC HEADER STRING",
+            &self.magic_header_string().trim_end_matches('\0'),
+        )?;
+        row("PAGE SIZE", &self.page_size_bytes())?;
+        row("FILE FORMAT WRITE VERSION", &self.file_format_write_version)?;
+        row("FILE FORMAT READ VERSION", &self.file_format_read_version)?;
+        row("JOURNAL MODE", &journal_mode)?;
+        row("RESERVED BYTES PER PAGE", &self.reserved_bytes_per_page)?;
+        row(
+            "MAXIMUM EMBEDDED PAYLOAD FRACTION",
+            &self.payload_fraction.maximum_embedded_fraction,
+        )?;
+        row(
+            "MINIMUM EMBEDDED PAYLOAD FRACTION",
+            &self.payload_fraction.minimum_embedded_fraction,
+        )?;
+        row("LEAF PAYLOAD FRACTION", &self.payload_fraction.leaf_fraction)?;
+        row("FILE CHANGE COUNTER", &self.file_change_counter)?;
+        row("IN-HEADER DATABASE SIZE", &self.in_header_database_size)?;
+        row("FREELIST PAGE INDEX", &self.freelist.page_index)?;
+        row("FREELIST COUNT", &self.freelist.count)?;
+        row("SCHEMA COOKIE", &self.schema.cookie)?;
+        row("SCHEMA FORMAT", &self.schema.format)?;
+        row("DEFAULT PAGE CACHE SIZE", &self.default_page_cache_size)?;
+        row("LARGEST ROOT B-TREE PAGE", &largest_root_btree_page)?;
+        row("DATABASE TEXT ENCODING", &self.database_text_encoding)?;
+        row("USER VERSION", &self.user_version)?;
+        row("INCREMENTAL-VACUUM MODE", &incremental_vacuum)?;
+        row("APPLICATION ID", &self.application_id)?;
+        row("VERSION VALID FOR NUMBER", &self.last_update.version_valid_for)?;
+        row("SQLITE VERSION NUMBER", &self.last_update.sqlite_version_number)?;
+        row("SQLITE VERSION", &self.last_update)
+    }
+
+    /// Lists the fields that changed from `self` to `other`, limited to the fields
+    /// that SQLite updates as a database is used.
+    #[cfg(feature = "alloc")]
+    pub fn diff(&self, other: &SQLite3Header) -> Vec<HeaderChange> {
+        let mut changes = Vec::new();
+        let mut compare = |old: u32, new: u32, change: fn(u32, u32) -> HeaderChange| {
+            if old != new {
+                changes.push(change(old, new));
+            }
+        };
+
+        compare(
+            self.file_change_counter,
+            other.file_change_counter,
+            |old, new| HeaderChange::FileChangeCounter { old, new },
+        );
+        compare(
+            self.in_header_database_size,
+            other.in_header_database_size,
+            |old, new| HeaderChange::InHeaderDatabaseSize { old, new },
+        );
+        compare(
+            self.freelist.page_index,
+            other.freelist.page_index,
+            |old, new| HeaderChange::FreelistPageIndex { old, new },
+        );
+        compare(
+            self.freelist.count,
+            other.freelist.count,
+            |old, new| HeaderChange::FreelistCount { old, new },
+        );
+        compare(
+            self.schema.cookie,
+            other.schema.cookie,
+            |old, new| HeaderChange::SchemaCookie { old, new },
+        );
+        compare(
+            self.user_version,
+            other.user_version,
+            |old, new| HeaderChange::UserVersion { old, new },
+        );
+        compare(
+            self.last_update.version_valid_for,
+            other.last_update.version_valid_for,
+            |old, new| HeaderChange::VersionValidFor { old, new },
+        );
+        compare(
+            self.last_update.sqlite_version_number,
+            other.last_update.sqlite_version_number,
+            |old, new| HeaderChange::SqliteVersionNumber { old, new },
+        );
+        changes
+    }
+}
+
+impl TryFrom<&[u8]> for SQLite3Header {
+    type Error = Error;
+
+    /// Parses a header the same way as [`SQLite3Header::from_bytes`].
+    ///
+    /// ```no_run
+    /// # use std::convert::TryInto;
+    /// # use sqlite3_header::SQLite3Header;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = std::fs::read("data.sqlite")?;
+    /// let header: SQLite3Header = bytes.as_slice().try_into()?;
+    /// println!("{}", header.page_size());
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl TryFrom<[u8; HEADER_SIZE]> for SQLite3Header {
+    type Error = Error;
+
+    /// Parses exactly the 100 header bytes, as returned by [`SQLite3Header::to_bytes`].
+    fn try_from(bytes: [u8; HEADER_SIZE]) -> Result<Self, Self::Error> {
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl FromStr for SQLite3Header {
+    type Err = Error;
+
+    /// Parses a header from a hex string, such as one copied from a hex dump.
+    /// Whitespace between digits is ignored. Like [`SQLite3Header::from_bytes`], any
+    /// bytes past the first 100 are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; HEADER_SIZE];
+        let mut digits = s
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .map(|b| (b as char).to_digit(16).ok_or(Error::InvalidHex));
+        let mut found = 0;
+        while let Some(high) = digits.next() {
+            let low = digits.next().ok_or(Error::InvalidHex)?;
+            if let Some(byte) = bytes.get_mut(found) {
+                *byte = (high? << 4 | low?) as u8;
+            } else {
+                high?;
+                low?;
+            }
+            found += 1;
+        }
+        if found < HEADER_SIZE {
+            return Err(Error::TooShort {
+                expected: HEADER_SIZE,
+                found,
+            });
+        }
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<File> for SQLite3Header {
+    type Error = Error;
+
+    /// Parses the header of an already open database file. Only the 100 header bytes
+    /// are read from the file's current position, which must be the start of the
+    /// database.
+    fn try_from(file: File) -> Result<Self, Self::Error> {
+        Self::from_reader(file)
+    }
+}
+
+impl Default for SQLite3Header {
+    /// The header SQLite writes for a new, empty database: 4096-byte pages in
+    /// rollback journal mode, schema format 4, UTF-8 text, no auto-vacuum, and zeroed
+    /// counters.
+    fn default() -> Self {
+        Self {
+            page_size: PageSize(4096),
+            file_format_write_version: FileFormat::Legacy,
+            file_format_read_version: FileFormat::Legacy,
+            reserved_bytes_per_page: 0,
+            payload_fraction: Payload {
+                leaf_fraction: 32,
+                maximum_embedded_fraction: 64,
+                minimum_embedded_fraction: 32,
+            },
+            file_change_counter: 0,
+            in_header_database_size: 0,
+            freelist: Freelist {
+                page_index: 0,
+                count: 0,
+            },
+            schema: Schema {
+                cookie: 0,
+                format: SchemaFormat::Format4,
+            },
+            default_page_cache_size: 0,
+            database_text_encoding: DatabaseTextEncoding::Utf8,
+            user_version: 0,
+            vacuum: None,
+            application_id: 0,
+            reserved_expansion: [0; 20],
+            last_update: LastUpdate {
+                sqlite_version_number: SUPPORTED_SQLITE_VERSION.to_number(),
+                version_valid_for: 0,
+            },
+        }
+    }
+}
+
+/// Debug-formats a value with its `Display` impl, for `Debug` output that shows
+/// decoded values.
+struct AsDisplay<'a, T>(&'a T);
+
+impl<T: fmt::Display> fmt::Debug for AsDisplay<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.0, f)
+    }
+}
+
+/// Debug-formats a group of fields of the header as a struct named `name`.
+struct Group<'a>(&'a str, &'a [(&'a str, &'a dyn fmt::Debug)]);
+
+impl fmt::Debug for Group<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut group = f.debug_struct(self.0);
+        for (name, value) in self.1 {
+            group.field(name, value);
+        }
+        group.finish()
+    }
+}
+
+impl fmt::Debug for SQLite3Header {
+    /// The compact form, `{:?}`, lists every field on one line. The pretty form,
+    /// `{:#?}`, groups related fields under `file_format`, `payload`, `freelist`,
+    /// `schema`, `vacuum`, and `last_update`. Both show the page size in bytes and
+    /// enums and versions as their `Display` strings.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_version = AsDisplay(&self.file_format_write_version);
+        let read_version = AsDisplay(&self.file_format_read_version);
+        let payload = &self.payload_fraction;
+        let schema_format = AsDisplay(&self.schema.format);
+        let (largest_root_btree_page, vacuum_mode) = match &self.vacuum {
+            Some(vacuum) => (vacuum.largest_root_btree_page, Some(AsDisplay(&vacuum.mode))),
+            None => (0, None),
+        };
+        let sqlite_version = AsDisplay(&self.last_update);
+
+        let pretty = f.alternate();
+        let mut header = f.debug_struct("SQLite3Header");
+        header.field("page_size", &AsDisplay(&self.page_size));
+        if !pretty {
+            return header
+                .field("file_format_write_version", &write_version)
+                .field("file_format_read_version", &read_version)
+                .field("reserved_bytes_per_page", &self.reserved_bytes_per_page)
+                .field("maximum_embedded_fraction", &payload.maximum_embedded_fraction)
+                .field("minimum_embedded_fraction", &payload.minimum_embedded_fraction)
+                .field("leaf_fraction", &payload.leaf_fraction)
+                .field("file_change_counter", &self.file_change_counter)
+                .field("in_header_database_size", &self.in_header_database_size)
+                .field("freelist_page_index", &self.freelist.page_index)
+                .field("freelist_count", &self.freelist.count)
+                .field("schema_cookie", &self.schema.cookie)
+                .field("schema_format", &schema_format)
+                .field("default_page_cache_size", &self.default_page_cache_size)
+                .field("database_text_encoding", &AsDisplay(&self.database_text_encoding))
+                .field("user_version", &self.user_version)
+                .field("largest_root_btree_page", &largest_root_btree_page)
+                .field("vacuum_mode", &vacuum_mode)
+                .field("application_id", &self.application_id)
+                .field("version_valid_for", &self.last_update.version_valid_for)
+                .field("sqlite_version", &sqlite_version)
+                .finish();
+        }
+
+        let vacuum_fields = vacuum_mode.as_ref().map(|mode| {
+            [
+                ("largest_root_btree_page", &largest_root_btree_page as &dyn fmt::Debug),
+                ("mode", mode),
+            ]
+        });
+        let vacuum = vacuum_fields.as_ref().map(|fields| Group("Vacuum", fields));
+        header
+            .field(
+                "file_format",
+                &Group("FileFormat", &[("write", &write_version), ("read", &read_version)]),
+            )
+            .field("reserved_bytes_per_page", &self.reserved_bytes_per_page)
+            .field(
+                "payload",
+                &Group(
+                    "Payload",
+                    &[
+                        ("maximum_embedded_fraction", &payload.maximum_embedded_fraction),
+                        ("minimum_embedded_fraction", &payload.minimum_embedded_fraction),
+                        ("leaf_fraction", &payload.leaf_fraction),
+                    ],
+                ),
+            )
+            .field("file_change_counter", &self.file_change_counter)
+            .field("in_header_database_size", &self.in_header_database_size)
+            .field(
+                "freelist",
+                &Group(
+                    "Freelist",
+                    &[
+                        ("page_index", &self.freelist.page_index),
+                        ("count", &self.freelist.count),
+                    ],
+                ),
+            )
+            .field(
+                "schema",
+                &Group(
+                    "Schema",
+                    &[("cookie", &self.schema.cookie), ("format", &schema_format)],
+                ),
+            )
+            .field("default_page_cache_size", &self.default_page_cache_size)
+            .field("database_text_encoding", &AsDisplay(&self.database_text_encoding))
+            .field("user_version", &self.user_version)
+            .field("vacuum", &vacuum)
+            .field("application_id", &self.application_id)
+            .field(
+                "last_update",
+                &Group(
+                    "LastUpdate",
+                    &[
+                        ("version_valid_for", &self.last_update.version_valid_for),
+                        ("sqlite_version", &sqlite_version),
+                    ],
+                ),
+            )
+            .finish()
+    }
+}
+
+impl fmt::Display for SQLite3Header {
+    /// Renders a labeled report of every header field, one per line, with the rows
+    /// of [`SQLite3Header::report_rows`].
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        self.visit_report_rows(|name, value| {
+            if !first {
+                f.write_str("\n")?;
+            }
+            first = false;
+            write!(f, "{}: {}", name, value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::*;
+
+    /// A 23-page database with 512-byte pages, a user version of 7, and 15 pages on
+    /// its freelist.
+    const SMALL_DB: &[u8] = include_bytes!("../tests/fixtures/small.db");
+
+    /// The bytes of a new database's header, for tests to modify.
+    fn header_bytes() -> [u8; HEADER_SIZE] {
+        SQLite3Header::default().to_bytes()
+    }
+
+    fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    #[test]
+    fn parses_fixture() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.page_size_bytes(), 512);
+        assert_eq!(header.file_format_write_version(), &FileFormat::Legacy);
+        assert_eq!(header.file_format_read_version(), &FileFormat::Legacy);
+        assert_eq!(header.reserved_bytes_per_page(), 0);
+        assert_eq!(header.file_change_counter(), 8);
+        assert_eq!(header.in_header_database_size(), 23);
+        assert_eq!(header.freelist(), &Freelist { page_index: 9, count: 15 });
+        assert_eq!(header.schema().cookie, 4);
+        assert_eq!(header.schema().format, SchemaFormat::Format4);
+        assert_eq!(header.database_text_encoding(), &DatabaseTextEncoding::Utf8);
+        assert_eq!(header.user_version(), 7);
+        assert_eq!(header.vacuum(), None);
+        assert_eq!(header.application_id(), 0);
+        assert_eq!(header.last_update().version_valid_for, 8);
+        assert_eq!(header.last_update().sqlite_version(), (3, 40, 1));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        assert_eq!(
+            SQLite3Header::from_bytes(&SMALL_DB[..50]),
+            Err(Error::TooShort { expected: 100, found: 50 }),
+        );
+        assert_eq!(
+            SQLite3Header::from_bytes(&[]),
+            Err(Error::TooShort { expected: 100, found: 0 }),
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_magic() {
+        let mut bytes = header_bytes();
+        bytes[..16].copy_from_slice(b"SQLite format 2\0");
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes),
+            Err(Error::InvalidMagicHeaderString(*b"SQLite format 2\0")),
+        );
+    }
+
+    #[test]
+    fn try_from_matches_from_bytes() {
+        let expected = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(SQLite3Header::try_from(SMALL_DB), Ok(expected.clone()));
+
+        let mut bytes = [0; HEADER_SIZE];
+        bytes.copy_from_slice(&SMALL_DB[..HEADER_SIZE]);
+        assert_eq!(SQLite3Header::try_from(bytes), Ok(expected));
+    }
+
+    #[test]
+    fn to_bytes_round_trips() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(&header.to_bytes()[..], &SMALL_DB[..HEADER_SIZE]);
+        assert_eq!(
+            SQLite3Header::from_bytes(&SQLite3Header::default().to_bytes()),
+            Ok(SQLite3Header::default()),
+        );
+    }
+
+    #[test]
+    fn decodes_page_size() {
+        let mut bytes = header_bytes();
+        bytes[16..18].copy_from_slice(&[0, 1]);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.page_size_bytes(), 65536);
+        assert_eq!(header.page_size().on_disk(), 1);
+        assert_eq!(header.to_bytes()[16..18], [0, 1]);
+
+        bytes[16..18].copy_from_slice(&1000u16.to_be_bytes());
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InvalidPageSize(1000)));
+        bytes[16..18].copy_from_slice(&256u16.to_be_bytes());
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InvalidPageSize(256)));
+    }
+
+    #[test]
+    fn page_size_newtype() {
+        assert_eq!(PageSize::new(65536).unwrap().on_disk(), 1);
+        assert_eq!(PageSize::new(4096).unwrap().bytes(), 4096);
+        assert_eq!(PageSize::from_on_disk(1).unwrap().bytes(), 65536);
+        assert_eq!(PageSize::new(0), Err(Error::InvalidPageSize(0)));
+        assert_eq!(PageSize::new(131072), Err(Error::InvalidPageSize(131072)));
+        assert_eq!(PageSize::try_from(512u16).map(u16::from), Ok(512));
+    }
+
+    #[test]
+    fn usable_size_floor() {
+        let mut bytes = header_bytes();
+        bytes[16..18].copy_from_slice(&512u16.to_be_bytes());
+        bytes[offsets::RESERVED_BYTES_PER_PAGE] = 32;
+        assert_eq!(SQLite3Header::from_bytes(&bytes).unwrap().usable_size(), 480);
+
+        bytes[offsets::RESERVED_BYTES_PER_PAGE] = 33;
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes),
+            Err(Error::InvalidReservedSpace { page_size: 512, reserved: 33 }),
+        );
+    }
+
+    #[test]
+    fn write_version_above_two_is_read_only() {
+        // A read version above 2 is rejected by parsing; see `rejects_newer_formats`.
+        for &(write, read, read_only) in &[
+            (1, 1, false),
+            (1, 2, false),
+            (2, 1, false),
+            (2, 2, false),
+            (3, 1, true),
+            (3, 2, true),
+            (255, 1, true),
+        ] {
+            let mut bytes = header_bytes();
+            bytes[offsets::FILE_FORMAT_WRITE_VERSION] = write;
+            bytes[offsets::FILE_FORMAT_READ_VERSION] = read;
+            let header = SQLite3Header::from_bytes(&bytes).unwrap();
+            assert_eq!(header.is_read_only(), read_only, "write {} read {}", write, read);
+            assert_eq!(header.is_accessible(), !read_only, "write {} read {}", write, read);
+            if read_only {
+                assert_eq!(header.file_format_write_version(), &FileFormat::Inaccessible);
+                assert_eq!(header.journal_mode(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_newer_formats() {
+        let mut bytes = header_bytes();
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 3;
+        let read_version = || Error::UnsupportedFormat {
+            field: "file format read version",
+            value: 3,
+        };
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(read_version()));
+        let view = SQLite3HeaderRef::new(&bytes).unwrap();
+        assert_eq!(view.file_format_read_version(), Err(read_version()));
+        assert_eq!(view.file_format_write_version(), Ok(FileFormat::Legacy));
+
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::SCHEMA_FORMAT, 7);
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes),
+            Err(Error::UnsupportedFormat { field: "schema format", value: 7 })
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn lossy_parse_reports_newer_formats() {
+        let mut bytes = header_bytes();
+        bytes[offsets::FILE_FORMAT_WRITE_VERSION] = 4;
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 4;
+        put_u32(&mut bytes, offsets::SCHEMA_FORMAT, 5);
+        let (header, issues) = SQLite3Header::from_bytes_lossy(&bytes);
+        assert_eq!(header.file_format_write_version, Some(FileFormat::Inaccessible));
+        assert_eq!(header.file_format_read_version, None);
+        assert_eq!(header.schema_format, None);
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::UnsupportedFormat { field: "file format read version", value: 4 },
+                ValidationIssue::UnsupportedFormat { field: "schema format", value: 5 },
+            ],
+        );
+
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 0;
+        put_u32(&mut bytes, offsets::SCHEMA_FORMAT, 0);
+        assert_eq!(
+            SQLite3Header::from_bytes_lossy(&bytes).1,
+            vec![
+                ValidationIssue::InvalidFileFormat {
+                    offset: offsets::FILE_FORMAT_READ_VERSION,
+                    value: 0,
+                },
+                ValidationIssue::InvalidSchemaFormat(0),
+            ],
+        );
+    }
+
+    #[test]
+    fn rejects_zero_file_format() {
+        let mut bytes = header_bytes();
+        bytes[offsets::FILE_FORMAT_WRITE_VERSION] = 0;
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InvalidFileFormat(0)));
+    }
+
+    #[test]
+    fn journal_mode() {
+        let mut bytes = header_bytes();
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes).unwrap().journal_mode(),
+            Some(JournalMode::Rollback),
+        );
+        bytes[offsets::FILE_FORMAT_WRITE_VERSION] = 2;
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 2;
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes).unwrap().journal_mode(),
+            Some(JournalMode::Wal),
+        );
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 1;
+        assert_eq!(SQLite3Header::from_bytes(&bytes).unwrap().journal_mode(), None);
+    }
+
+    #[test]
+    fn default_page_cache_size_is_signed() {
+        let mut bytes = header_bytes();
+        bytes[48..52].copy_from_slice(&(-2000i32).to_be_bytes());
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.default_page_cache_size(), -2000);
+        assert_eq!(header.suggested_cache_size_pages(), 2000);
+    }
+
+    #[test]
+    fn rejects_payload_fractions() {
+        for &(offset, expected) in &PAYLOAD_FRACTIONS {
+            let mut bytes = header_bytes();
+            bytes[offset] = 16;
+            assert_eq!(
+                SQLite3Header::from_bytes(&bytes),
+                Err(Error::InvalidPayloadFraction { offset, expected, found: 16 }),
+            );
+        }
+    }
+
+    #[test]
+    fn reserved_expansion() {
+        let mut bytes = header_bytes();
+        bytes[80] = 0xab;
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert!(!header.reserved_expansion_is_zero());
+        assert_eq!(header.reserved_expansion()[8], 0xab);
+        assert_eq!(header.to_bytes(), bytes);
+        assert_eq!(
+            SQLite3Header::from_bytes_strict(&bytes),
+            Err(Error::NonZeroReserved { offset: 80 }),
+        );
+        assert!(SQLite3Header::from_bytes_strict(SMALL_DB).is_ok());
+        assert!(SQLite3Header::default().reserved_expansion_is_zero());
+    }
+
+    #[test]
+    fn in_header_database_size_validity() {
+        let mut header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert!(header.in_header_database_size_valid());
+        assert_eq!(header.database_size_pages(), Some(23));
+        assert!(header.trusts_in_header_size());
+
+        header.bump_change_counter();
+        assert!(!header.in_header_database_size_valid());
+        assert_eq!(header.database_size_pages(), None);
+        assert_eq!(header.database_size_or_filesize(512 * 30), 30);
+        assert!(!header.trusts_in_header_size());
+
+        header.set_version_valid_for(header.file_change_counter());
+        assert_eq!(header.database_size_pages(), Some(23));
+
+        // A zero size is never valid, even with matching counters.
+        assert_eq!(SQLite3Header::default().database_size_pages(), None);
+    }
+
+    #[test]
+    fn trusts_in_header_size_only_since_3_7_0() {
+        let mut bytes = SMALL_DB[..HEADER_SIZE].to_vec();
+        put_u32(&mut bytes, offsets::SQLITE_VERSION_NUMBER, 3_006_023);
+        assert!(!SQLite3Header::from_bytes(&bytes).unwrap().trusts_in_header_size());
+        put_u32(&mut bytes, offsets::SQLITE_VERSION_NUMBER, 3_007_000);
+        assert!(SQLite3Header::from_bytes(&bytes).unwrap().trusts_in_header_size());
+        put_u32(&mut bytes, offsets::IN_HEADER_DATABASE_SIZE, 0);
+        assert!(!SQLite3Header::from_bytes(&bytes).unwrap().trusts_in_header_size());
+    }
+
+    #[test]
+    fn check_size_consistency() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(
+            header.check_size_consistency(SMALL_DB.len() as u64),
+            SizeConsistency::Consistent,
+        );
+        assert_eq!(
+            header.check_size_consistency(512 * 20),
+            SizeConsistency::Mismatch { in_header: 23, actual: 20 },
+        );
+
+        let mut stale = header.clone();
+        stale.bump_change_counter();
+        assert_eq!(stale.check_size_consistency(512 * 23), SizeConsistency::Stale);
+        assert_eq!(SQLite3Header::default().check_size_consistency(0), SizeConsistency::Unused);
+    }
+
+    #[test]
+    fn page_count_and_free_page_ratio() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.page_count(SMALL_DB.len() as u64), 23);
+        assert_eq!(header.page_count(511), 0);
+        assert_eq!(header.free_page_ratio(512 * 30), Some(0.5));
+        assert_eq!(header.free_page_ratio(512 * 10), Some(1.0));
+        assert_eq!(header.free_page_ratio(0), None);
+    }
+
+    #[test]
+    fn sqlite_version() {
+        let version = SqliteVersion::from_number(3_044_002);
+        assert_eq!(version, SqliteVersion { major: 3, minor: 44, patch: 2 });
+        assert_eq!(version.to_number(), 3_044_002);
+        assert_eq!(version.to_string(), "3.44.2");
+        assert_eq!(SqliteVersion::from_number(0).to_string(), "0.0.0");
+        assert!(SqliteVersion::from_number(3_045_000) > version);
+
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.last_update().to_string(), "3.40.1");
+        assert!(!header.written_by_newer_sqlite());
+
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::SQLITE_VERSION_NUMBER, 3_046_000);
+        assert!(SQLite3Header::from_bytes(&bytes).unwrap().written_by_newer_sqlite());
+    }
+
+    #[test]
+    fn text_encoding() {
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::DATABASE_TEXT_ENCODING, 2);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.database_text_encoding(), &DatabaseTextEncoding::Utf16le);
+        assert_eq!(header.database_text_encoding().as_str(), "UTF-16le");
+        assert_eq!(DatabaseTextEncoding::Utf16be.to_string(), "UTF-16be");
+
+        put_u32(&mut bytes, offsets::DATABASE_TEXT_ENCODING, 4);
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InvalidTextEncoding(4)));
+    }
+
+    #[test]
+    fn vacuum_consistency() {
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::INCREMENTAL_VACUUM_MODE, 1);
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InconsistentVacuumState(1)));
+
+        put_u32(&mut bytes, offsets::LARGEST_ROOT_BTREE_PAGE, 5);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            header.vacuum(),
+            Some(&Vacuum { largest_root_btree_page: 5, mode: VacuumMode::Incremental }),
+        );
+        assert!(header.is_auto_vacuum());
+        assert!(header.is_incremental_vacuum());
+
+        put_u32(&mut bytes, offsets::INCREMENTAL_VACUUM_MODE, 0);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.vacuum().map(|vacuum| vacuum.mode), Some(VacuumMode::Auto));
+        assert!(!header.is_incremental_vacuum());
+    }
+
+    #[test]
+    fn schema_format() {
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::SCHEMA_FORMAT, 1);
+        let format = SQLite3Header::from_bytes(&bytes).unwrap().schema().format;
+        assert_eq!(format, SchemaFormat::Format1);
+        assert_eq!(format.number(), 1);
+        assert!(!format.respects_desc_indexes());
+        assert!(SchemaFormat::Format4.respects_desc_indexes());
+
+        put_u32(&mut bytes, offsets::SCHEMA_FORMAT, 0);
+        assert_eq!(SQLite3Header::from_bytes(&bytes), Err(Error::InvalidSchemaFormat(0)));
+    }
+
+    #[test]
+    fn freelist_accessors() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.freelist().first_page(), NonZeroU32::new(9));
+        assert!(!header.freelist().is_empty());
+        assert_eq!(SQLite3Header::default().freelist().first_page(), None);
+        assert!(SQLite3Header::default().freelist().is_empty());
+    }
+
+    #[test]
+    fn freelist_validate_against() {
+        let freelist = Freelist { page_index: 9, count: 15 };
+        assert_eq!(freelist.validate_against(23), Ok(()));
+        assert_eq!(freelist.validate_against(8), Err(Error::PageOutOfBounds(9)));
+        assert_eq!(
+            freelist.validate_against(12),
+            Err(Error::InvalidFreelistCount { count: 15, page_count: 12 }),
+        );
+    }
+
+    #[test]
+    fn freelist_pages() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        let pages = header.freelist().pages(SMALL_DB, 512).collect::<Result<Vec<_>, _>>();
+        assert_eq!(pages, Ok((9..=23).collect()));
+    }
+
+    /// A database of `pages` 512-byte pages with the given reserved space, whose
+    /// trunk pages are given as `(page, next trunk, leaves)`.
+    fn freelist_db(pages: usize, reserved: u8, trunks: &[(usize, u32, &[u32])]) -> Vec<u8> {
+        let mut db = vec![0; pages * 512];
+        db[..HEADER_SIZE].copy_from_slice(&header_bytes());
+        db[offsets::RESERVED_BYTES_PER_PAGE] = reserved;
+        for &(page, next, leaves) in trunks {
+            let trunk = &mut db[(page - 1) * 512..page * 512];
+            put_u32(trunk, 0, next);
+            put_u32(trunk, 4, leaves.len() as u32);
+            for (i, &leaf) in leaves.iter().enumerate() {
+                put_u32(trunk, 8 + i * 4, leaf);
+            }
+        }
+        db
+    }
+
+    #[test]
+    fn freelist_pages_follow_trunks() {
+        let db = freelist_db(5, 0, &[(2, 4, &[3, 5]), (4, 0, &[])]);
+        let freelist = Freelist { page_index: 2, count: 4 };
+        let pages = freelist.pages(&db, 512).collect::<Result<Vec<_>, _>>();
+        assert_eq!(pages, Ok(vec![2, 3, 5, 4]));
+
+        // The count stops a cycle.
+        let db = freelist_db(3, 0, &[(2, 3, &[]), (3, 2, &[])]);
+        let freelist = Freelist { page_index: 2, count: 5 };
+        assert_eq!(freelist.pages(&db, 512).count(), 5);
+    }
+
+    #[test]
+    fn freelist_trunks_are_bounded_by_usable_size() {
+        let leaves = [3; 125];
+        let freelist = Freelist { page_index: 2, count: 2 };
+        let db = freelist_db(3, 0, &[(2, 0, &leaves)]);
+        assert_eq!(freelist.pages(&db, 512).collect::<Result<Vec<_>, _>>(), Ok(vec![2, 3]));
+
+        let db = freelist_db(3, 8, &[(2, 0, &leaves)]);
+        assert_eq!(
+            freelist.pages(&db, 512).collect::<Vec<_>>(),
+            [Err(Error::InvalidFreelistTrunk(2))]
+        );
+    }
+
+    #[test]
+    fn freelist_pages_reject_tiny_page_sizes() {
+        let freelist = Freelist { page_index: 1, count: 1 };
+        for page_size in [0, 4, 7] {
+            assert_eq!(
+                freelist.pages(SMALL_DB, page_size).collect::<Vec<_>>(),
+                [Err(Error::InvalidFreelistTrunk(1))]
+            );
+        }
+    }
+
+    #[test]
+    fn from_bytes_at_offset() {
+        let mut file = vec![0xff; 37];
+        file.extend_from_slice(SMALL_DB);
+        assert_eq!(
+            SQLite3Header::from_bytes_at(&file, 37),
+            SQLite3Header::from_bytes(SMALL_DB),
+        );
+        assert_eq!(
+            SQLite3Header::from_bytes_at(&file, file.len() - 10),
+            Err(Error::TooShort { expected: file.len() + 90, found: file.len() }),
+        );
+        assert_eq!(
+            SQLite3Header::from_bytes_at(&file, usize::MAX),
+            Err(Error::TooShort { expected: usize::MAX, found: file.len() }),
+        );
+    }
+
+    #[test]
+    fn finds_appended_database() {
+        let mut file = b"an executable, say".to_vec();
+        let offset = file.len() as u64;
+        file.extend_from_slice(SMALL_DB);
+        file.extend_from_slice(APPEND_MARK);
+        file.extend_from_slice(&offset.to_be_bytes());
+
+        assert_eq!(find_appended_database(&file), Some(offset));
+        assert!(SQLite3Header::from_bytes_at(&file, offset as usize).is_ok());
+        assert_eq!(find_appended_database(SMALL_DB), None);
+        assert_eq!(find_appended_database(b"short"), None);
+
+        let len = file.len();
+        file[len - 8..].copy_from_slice(&(len as u64).to_be_bytes());
+        assert_eq!(find_appended_database(&file), None);
+    }
+
+    #[test]
+    fn default_header() {
+        let header = SQLite3Header::default();
+        assert_eq!(header.page_size_bytes(), 4096);
+        assert_eq!(header.journal_mode(), Some(JournalMode::Rollback));
+        assert_eq!(header.schema().format, SchemaFormat::Format4);
+        assert_eq!(header.database_text_encoding(), &DatabaseTextEncoding::Utf8);
+        assert_eq!(header.last_update().version(), SUPPORTED_SQLITE_VERSION);
+        assert_eq!(header.magic_header_string(), SQLite3Header::MAGIC);
+        assert_eq!(SQLite3Header::MAGIC.as_bytes(), &MAGIC_HEADER_BYTES);
+    }
+
+    #[test]
+    fn setters_are_serialized() {
+        let mut header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        header.set_user_version(0x0102_0304);
+        header.set_application_id(0x4d50_4258);
+        let bytes = header.to_bytes();
+        assert_eq!(bytes[60..64], [1, 2, 3, 4]);
+        assert_eq!(&bytes[68..72], b"MPBX");
+        assert_eq!(bytes[..60], SMALL_DB[..60]);
+        assert_eq!(bytes[72..HEADER_SIZE], SMALL_DB[72..HEADER_SIZE]);
+        assert_eq!(SQLite3Header::from_bytes(&bytes).unwrap().user_version(), 0x0102_0304);
+    }
+
+    #[test]
+    fn bump_change_counter_wraps() {
+        let mut bytes = header_bytes();
+        put_u32(&mut bytes, offsets::FILE_CHANGE_COUNTER, u32::MAX);
+        let mut header = SQLite3Header::from_bytes(&bytes).unwrap();
+        header.bump_change_counter();
+        assert_eq!(header.file_change_counter(), 0);
+    }
+
+    #[test]
+    fn likely_encrypted() {
+        let encrypted = |reserved| {
+            let mut bytes = header_bytes();
+            bytes[offsets::RESERVED_BYTES_PER_PAGE] = reserved;
+            SQLite3Header::from_bytes(&bytes).unwrap().likely_encrypted()
+        };
+        assert!(!encrypted(0));
+        assert!(!encrypted(8));
+        assert!(encrypted(16));
+        assert!(!encrypted(48));
+        assert!(!encrypted(80));
+        assert!(!SQLite3Header::from_bytes(SMALL_DB).unwrap().likely_encrypted());
+    }
+
+    #[test]
+    fn application_id_name() {
+        let mut header = SQLite3Header::default();
+        assert_eq!(header.application_id_name(), None);
+        header.set_application_id(0x0f05_5112);
+        assert_eq!(header.application_id_name(), Some("Fossil checkout"));
+        header.set_application_id(u32::from_be_bytes(*b"GP11"));
+        assert_eq!(header.application_id_name(), Some("GeoPackage 1.1"));
+        header.set_application_id(0x1234_5678);
+        assert_eq!(header.application_id_name(), None);
+    }
+
+    #[test]
+    fn geopackage_version() {
+        let mut header = SQLite3Header::default();
+        assert_eq!(header.geopackage_version(), None);
+        header.set_application_id(u32::from_be_bytes(*b"GP10"));
+        assert_eq!(header.geopackage_version(), Some((1, 0, 0)));
+        header.set_application_id(u32::from_be_bytes(*b"GP11"));
+        assert_eq!(header.geopackage_version(), Some((1, 1, 0)));
+        header.set_application_id(u32::from_be_bytes(*b"GPKG"));
+        assert_eq!(header.geopackage_version(), None);
+        header.set_user_version(10300);
+        assert_eq!(header.geopackage_version(), Some((1, 3, 0)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn describe() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(
+            header.describe(),
+            "SQLite 3.x database, page size 512, UTF-8, rollback, last written by 3.40.1",
+        );
+
+        let mut bytes = header_bytes();
+        bytes[offsets::FILE_FORMAT_WRITE_VERSION] = 2;
+        bytes[offsets::FILE_FORMAT_READ_VERSION] = 2;
+        put_u32(&mut bytes, offsets::APPLICATION_ID, 0x4750_4b47);
+        assert_eq!(
+            SQLite3Header::from_bytes(&bytes).unwrap().describe(),
+            "SQLite 3.x database, page size 4096, UTF-8, WAL, last written by 3.45.0, \
+             application id GeoPackage",
+        );
+
+        let mut header = SQLite3Header::default();
+        header.set_application_id(0x4d50_4258);
+        assert!(header.describe().ends_with(", application id MBTiles tileset"));
+        header.set_application_id(0x1234);
+        assert!(header.describe().ends_with(", application id 0x00001234"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn lossy_parse_of_truncated_header() {
+        let (header, issues) = SQLite3Header::from_bytes_lossy(&SMALL_DB[..30]);
+        assert_eq!(header.page_size, Some(512));
+        assert_eq!(header.file_format_read_version, Some(FileFormat::Legacy));
+        assert_eq!(header.file_change_counter, Some(8));
+        assert_eq!(header.in_header_database_size, None);
+        assert_eq!(header.sqlite_version_number, None);
+        assert_eq!(issues, vec![ValidationIssue::TooShort { expected: 100, found: 30 }]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn lossy_parse_collects_every_issue() {
+        let mut bytes = header_bytes();
+        bytes[0] = b's';
+        bytes[offsets::LEAF_PAYLOAD_FRACTION] = 0;
+        put_u32(&mut bytes, offsets::DATABASE_TEXT_ENCODING, 9);
+        put_u32(&mut bytes, offsets::FILE_CHANGE_COUNTER, 1);
+
+        let (header, issues) = SQLite3Header::from_bytes_lossy(&bytes);
+        assert_eq!(header.database_text_encoding, None);
+        assert_eq!(header.leaf_payload_fraction, Some(0));
+        let mut magic = MAGIC_HEADER_BYTES;
+        magic[0] = b's';
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::InvalidMagicHeaderString(magic),
+                ValidationIssue::InvalidPayloadFraction {
+                    offset: offsets::LEAF_PAYLOAD_FRACTION,
+                    expected: 32,
+                    found: 0,
+                },
+                ValidationIssue::InvalidTextEncoding(9),
+                ValidationIssue::StaleInHeaderDatabaseSize {
+                    file_change_counter: 1,
+                    version_valid_for: 0,
+                },
+            ],
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn validate() {
+        assert_eq!(SQLite3Header::from_bytes(SMALL_DB).unwrap().validate(), vec![]);
+
+        let mut bytes = header_bytes();
+        bytes[72] = 1;
+        let mut header = SQLite3Header::from_bytes(&bytes).unwrap();
+        header.bump_change_counter();
+        assert_eq!(
+            header.validate(),
+            vec![
+                ValidationIssue::NonZeroReserved { offset: 72 },
+                ValidationIssue::StaleInHeaderDatabaseSize {
+                    file_change_counter: 1,
+                    version_valid_for: 0,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn from_hex_str() {
+        let hex = |bytes: &[u8]| -> String {
+            bytes
+                .chunks(16)
+                .map(|line| line.iter().map(|b| format!("{:02x} ", b)).collect::<String>() + "\n")
+                .collect()
+        };
+        assert_eq!(hex(SMALL_DB).parse::<SQLite3Header>(), SQLite3Header::from_bytes(SMALL_DB));
+        assert_eq!(
+            hex(&SMALL_DB[..99]).parse::<SQLite3Header>(),
+            Err(Error::TooShort { expected: 100, found: 99 }),
+        );
+        assert_eq!("53 5".parse::<SQLite3Header>(), Err(Error::InvalidHex));
+        assert_eq!("zz".parse::<SQLite3Header>(), Err(Error::InvalidHex));
+    }
+
+    #[test]
+    fn local_payload_limits() {
+        let header = SQLite3Header::default();
+        assert_eq!(header.max_local_payload(btree::PageType::LeafTable), 4061);
+        assert_eq!(header.max_local_payload(btree::PageType::LeafIndex), 1002);
+        assert_eq!(header.min_local_payload(), 489);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn diff() {
+        let old = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(old.diff(&old.clone()), vec![]);
+
+        let mut bytes = old.to_bytes();
+        put_u32(&mut bytes, offsets::FILE_CHANGE_COUNTER, 9);
+        put_u32(&mut bytes, offsets::FREELIST_COUNT, 14);
+        let new = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                HeaderChange::FileChangeCounter { old: 8, new: 9 },
+                HeaderChange::FreelistCount { old: 15, new: 14 },
+            ],
+        );
+    }
+
+    #[test]
+    fn schema_changed_since() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert!(!header.schema_changed_since(4));
+        assert!(header.schema_changed_since(3));
+    }
+
+    #[test]
+    fn fingerprint_ignores_changing_fields() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        let mut written = header.clone();
+        written.bump_change_counter();
+        written.set_user_version(100);
+        assert_eq!(header.fingerprint(), written.fingerprint());
+        assert_ne!(header, written);
+
+        let mut other = header.clone();
+        other.set_application_id(1);
+        assert_ne!(header.fingerprint(), other.fingerprint());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn usable_as_hash_key() {
+        use std::collections::HashSet;
+
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        let mut seen = HashSet::new();
+        assert!(seen.insert(header.clone()));
+        assert!(!seen.insert(header));
+        assert!(seen.insert(SQLite3Header::default()));
+    }
+
+    #[test]
+    fn peek() {
+        assert_eq!(
+            super::peek(SMALL_DB),
+            Ok((PageSize::new(512).unwrap(), DatabaseTextEncoding::Utf8)),
+        );
+        assert_eq!(super::peek(&SMALL_DB[..99]), Err(Error::TooShort { expected: 100, found: 99 }));
+
+        // Fields other than the page size and encoding aren't checked.
+        let mut bytes = header_bytes();
+        bytes[offsets::LEAF_PAYLOAD_FRACTION] = 0;
+        assert!(super::peek(&bytes).is_ok());
+        bytes[0] = 0;
+        assert!(matches!(super::peek(&bytes), Err(Error::InvalidMagicHeaderString(_))));
+    }
+
+    #[test]
+    fn with_raw() {
+        let (header, raw) = SQLite3Header::from_bytes_with_raw(SMALL_DB).unwrap();
+        assert_eq!(Ok(header), SQLite3Header::from_bytes(SMALL_DB));
+        assert_eq!(&raw[..], &SMALL_DB[..HEADER_SIZE]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn annotate() {
+        let fields = super::annotate(SMALL_DB);
+        assert_eq!(fields.len(), offsets::FIELDS.len());
+        assert_eq!(fields[0], (0..16, fields[0].1, "\"SQLite format 3\\0\"".to_string()));
+        assert!(fields.contains(&(16..18, fields[1].1, "512".to_string())));
+
+        // Fields past the end of the input are left out.
+        assert_eq!(super::annotate(&SMALL_DB[..18]).len(), 2);
+    }
+
+    #[test]
+    fn schema_root_offset() {
+        assert_eq!(SQLite3Header::default().schema_root_offset(), 100);
+    }
+
+    #[test]
+    fn page_content_offset() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.page_content_offset(0), None);
+        assert_eq!(header.page_content_offset(1), Some(100));
+        assert_eq!(header.page_content_offset(2), Some(512));
+        assert_eq!(header.page_content_offset(u32::MAX), Some(u64::from(u32::MAX - 1) * 512));
+    }
+
+    #[test]
+    fn lock_byte_page_number() {
+        let mut bytes = header_bytes();
+        assert_eq!(SQLite3Header::default().lock_byte_page_number(), 262_145);
+        bytes[16..18].copy_from_slice(&[0, 1]);
+        assert_eq!(SQLite3Header::from_bytes(&bytes).unwrap().lock_byte_page_number(), 16_385);
+    }
+
+    #[test]
+    fn lock_byte_page() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        assert_eq!(header.lock_byte_page(), None);
+        assert_eq!(header.lock_byte_page_in_file(SMALL_DB.len() as u64), None);
+        // The valid in-header size of 23 pages takes precedence over the file length.
+        assert_eq!(header.lock_byte_page_in_file(1 << 31), None);
+
+        let mut bytes = header_bytes();
+        bytes[16..18].copy_from_slice(&[0, 1]);
+        put_u32(&mut bytes, offsets::FILE_CHANGE_COUNTER, 3);
+        put_u32(&mut bytes, offsets::VERSION_VALID_FOR, 3);
+        put_u32(&mut bytes, offsets::IN_HEADER_DATABASE_SIZE, 16_384);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.lock_byte_page(), None);
+        put_u32(&mut bytes, offsets::IN_HEADER_DATABASE_SIZE, 16_385);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.lock_byte_page(), Some(16_385));
+        assert_eq!(header.lock_byte_page_in_file(0), Some(16_385));
+
+        // Without a valid in-header size, only the file length can tell.
+        put_u32(&mut bytes, offsets::IN_HEADER_DATABASE_SIZE, 0);
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        assert_eq!(header.lock_byte_page(), None);
+        assert_eq!(header.lock_byte_page_in_file(16_384 * 65_536), None);
+        assert_eq!(header.lock_byte_page_in_file(16_385 * 65_536), Some(16_385));
+    }
+
+    #[test]
+    fn ptrmap_pages_skip_the_lock_byte_page() {
+        // With 1024-byte pages and 254 reserved bytes, each ptrmap page covers the 154
+        // pages after it, and the ptrmap page 6765 * 155 + 2 is the lock-byte page.
+        let mut bytes = header_bytes();
+        bytes[16..18].copy_from_slice(&1024u16.to_be_bytes());
+        bytes[offsets::RESERVED_BYTES_PER_PAGE] = 254;
+        let header = SQLite3Header::from_bytes(&bytes).unwrap();
+        let lock_byte_page = header.lock_byte_page_number();
+        assert_eq!(lock_byte_page, 6765 * 155 + 2);
+
+        assert_eq!(vacuum::ptrmap_page_for(lock_byte_page, 770), lock_byte_page);
+        assert_eq!(header.ptrmap_page_for(lock_byte_page), lock_byte_page + 1);
+        assert_eq!(header.ptrmap_page_for(lock_byte_page + 154), lock_byte_page + 1);
+        assert_eq!(header.ptrmap_page_for(lock_byte_page + 155), lock_byte_page + 155);
+        assert_eq!(header.ptrmap_page_for(lock_byte_page - 1), lock_byte_page - 155);
+        assert!(!header.is_ptrmap_page(lock_byte_page));
+        assert!(header.is_ptrmap_page(lock_byte_page + 1));
+
+        // Below 1 GiB the header agrees with the page-size-independent functions.
+        for page_no in 0..1000 {
+            assert_eq!(header.ptrmap_page_for(page_no), vacuum::ptrmap_page_for(page_no, 770));
+            assert_eq!(header.is_ptrmap_page(page_no), vacuum::is_ptrmap_page(page_no, 770));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn report_rows_match_display() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        let rows = header.report_rows();
+        assert_eq!(rows.len(), 24);
+        assert_eq!(rows[0], ("MAGIC HEADER STRING", "SQLite format 3".to_string()));
+        assert_eq!(rows[1], ("PAGE SIZE", "512".to_string()));
+        assert_eq!(rows[4], ("JOURNAL MODE", "rollback".to_string()));
+        assert_eq!(rows[23], ("SQLITE VERSION", "3.40.1".to_string()));
+
+        let lines = rows
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>();
+        assert_eq!(header.to_string(), lines.join("\n"));
+    }
+
+    #[test]
+    fn debug_hides_reserved_expansion() {
+        let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+        let compact = format!("{:?}", header);
+        assert!(compact.starts_with("SQLite3Header { page_size: 512, "));
+        assert!(compact.contains("sqlite_version: 3.40.1"));
+        assert!(!compact.contains("reserved_expansion"));
+
+        let pretty = format!("{:#?}", header);
+        assert!(pretty.contains("    freelist: Freelist {\n        page_index: 9,\n"));
+        assert!(!pretty.contains("reserved_expansion"));
+    }
+
+    #[test]
+    fn display_enums() {
+        assert_eq!(FileFormat::WriteAheadLogging.to_string(), "WAL");
+        assert_eq!(FileFormat::Legacy.to_string(), "legacy");
+        assert_eq!(SchemaFormat::Format3.to_string(), "3");
+        assert_eq!(VacuumMode::Incremental.to_string(), "incremental_vacuum");
+        assert_eq!(PageSize::new(65536).unwrap().to_string(), "65536");
+    }
+
+    /// A fixed pseudo-random sequence, so that failures are reproducible.
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn parse_fuzz_never_panics() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..20_000 {
+            let mut bytes = SMALL_DB[..HEADER_SIZE].to_vec();
+            for _ in 0..xorshift(&mut state) % 8 {
+                let at = (xorshift(&mut state) % HEADER_SIZE as u64) as usize;
+                bytes[at] = xorshift(&mut state) as u8;
+            }
+            bytes.truncate((xorshift(&mut state) % 128) as usize);
+            let _ = parse_fuzz(&bytes);
+            #[cfg(feature = "alloc")]
+            let _ = SQLite3Header::from_bytes_lossy(&bytes);
+            let _ = SQLite3HeaderRef::new(&bytes).map(|header| header.to_owned());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use std::string::ToString;
+
+        use super::*;
+
+        const SMALL_DB_JSON: &str = concat!(
+            r#"{"page_size":512,"file_format_write_version":"Legacy","#,
+            r#""file_format_read_version":"Legacy","reserved_bytes_per_page":0,"#,
+            r#""payload_fraction":{"leaf_fraction":32,"maximum_embedded_fraction":64,"#,
+            r#""minimum_embedded_fraction":32},"file_change_counter":8,"#,
+            r#""in_header_database_size":23,"freelist":{"page_index":9,"count":15},"#,
+            r#""schema":{"cookie":4,"format":"Format4"},"default_page_cache_size":0,"#,
+            r#""database_text_encoding":"Utf8","user_version":7,"vacuum":null,"#,
+            r#""application_id":0,"reserved_expansion":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],"#,
+            r#""last_update":{"sqlite_version_number":3040001,"version_valid_for":8}}"#,
+        );
+
+        fn with(field: &str, value: &str) -> serde_json::Value {
+            let mut json: serde_json::Value = serde_json::from_str(SMALL_DB_JSON).unwrap();
+            let mut target = &mut json;
+            for key in field.split('.') {
+                target = &mut target[key];
+            }
+            *target = serde_json::from_str(value).unwrap();
+            json
+        }
+
+        fn parse(json: serde_json::Value) -> Result<SQLite3Header, std::string::String> {
+            serde_json::from_value(json).map_err(|e| e.to_string())
+        }
+
+        #[test]
+        fn json_shape() {
+            let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+            assert_eq!(serde_json::to_string(&header).unwrap(), SMALL_DB_JSON);
+            assert_eq!(serde_json::from_str(SMALL_DB_JSON).ok(), Some(header));
+        }
+
+        #[test]
+        fn round_trips_every_field() {
+            let mut bytes = header_bytes();
+            bytes[offsets::FILE_FORMAT_WRITE_VERSION] = 2;
+            bytes[offsets::FILE_FORMAT_READ_VERSION] = 2;
+            bytes[offsets::RESERVED_BYTES_PER_PAGE] = 8;
+            put_u32(&mut bytes, offsets::LARGEST_ROOT_BTREE_PAGE, 4);
+            put_u32(&mut bytes, offsets::INCREMENTAL_VACUUM_MODE, 1);
+            put_u32(&mut bytes, offsets::DATABASE_TEXT_ENCODING, 3);
+            put_u32(&mut bytes, offsets::DEFAULT_PAGE_CACHE_SIZE, -2000i32 as u32);
+            bytes[offsets::RESERVED] = 1;
+            let header = SQLite3Header::from_bytes(&bytes).unwrap();
+            assert_eq!(parse(serde_json::to_value(&header).unwrap()), Ok(header));
+        }
+
+        #[test]
+        fn page_size_is_in_bytes() {
+            let mut bytes = header_bytes();
+            bytes[offsets::PAGE_SIZE..offsets::PAGE_SIZE + 2].copy_from_slice(&[0, 1]);
+            let header = SQLite3Header::from_bytes(&bytes).unwrap();
+            let json = serde_json::to_value(&header).unwrap();
+            assert_eq!(json["page_size"], 65536);
+            assert_eq!(parse(json), Ok(header));
+
+            assert_eq!(parse(with("page_size", "1")), Err(Error::InvalidPageSize(1).to_string()));
+            assert_eq!(serde_json::to_string(&PageSize::new(65536).unwrap()).unwrap(), "65536");
+        }
+
+        #[test]
+        fn missing_reserved_expansion_is_zeroed() {
+            let mut json: serde_json::Value = serde_json::from_str(SMALL_DB_JSON).unwrap();
+            json.as_object_mut().unwrap().remove("reserved_expansion");
+            assert_eq!(parse(json), Ok(SQLite3Header::from_bytes(SMALL_DB).unwrap()));
+        }
+
+        #[test]
+        fn rejects_what_parsing_rejects() {
+            let error = |e: Error| Err(e.to_string());
+            assert_eq!(parse(with("page_size", "1024")).map(|h| h.page_size_bytes()), Ok(1024));
+            assert_eq!(parse(with("page_size", "1000")), error(Error::InvalidPageSize(1000)));
+            assert_eq!(
+                parse(with("reserved_bytes_per_page", "33")),
+                error(Error::InvalidReservedSpace { page_size: 512, reserved: 33 }),
+            );
+            assert_eq!(
+                parse(with("payload_fraction.leaf_fraction", "0")),
+                error(Error::InvalidPayloadFraction {
+                    offset: offsets::LEAF_PAYLOAD_FRACTION,
+                    expected: 32,
+                    found: 0,
+                }),
+            );
+            assert_eq!(
+                parse(with("vacuum", r#"{"largest_root_btree_page":0,"mode":"Auto"}"#)),
+                error(Error::InconsistentVacuumState(0)),
+            );
+            assert!(parse(with("vacuum", r#"{"largest_root_btree_page":3,"mode":"Auto"}"#))
+                .is_ok());
+        }
+
+        #[test]
+        fn payload_rejects_tuned_fractions() {
+            let payload: Result<Payload, _> = serde_json::from_str(concat!(
+                r#"{"leaf_fraction":32,"maximum_embedded_fraction":60,"#,
+                r#""minimum_embedded_fraction":32}"#,
+            ));
+            assert_eq!(
+                payload.unwrap_err().to_string(),
+                Error::InvalidPayloadFraction {
+                    offset: offsets::MAXIMUM_EMBEDDED_PAYLOAD_FRACTION,
+                    expected: 64,
+                    found: 60,
+                }
+                .to_string(),
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod io {
+        use std::{
+            fs::File,
+            io::{Cursor, Read, Seek, SeekFrom, Write},
+        };
+
+        use super::*;
+
+        const SMALL_DB_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/small.db");
+
+        /// A reader that returns at most `chunk` bytes per read and is interrupted
+        /// before each one.
+        struct Trickle<'a> {
+            bytes: &'a [u8],
+            chunk: usize,
+            interrupt: bool,
+        }
+
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.interrupt = !self.interrupt;
+                if self.interrupt {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                let n = buf.len().min(self.chunk).min(self.bytes.len());
+                buf[..n].copy_from_slice(&self.bytes[..n]);
+                self.bytes = &self.bytes[n..];
+                Ok(n)
+            }
+        }
+
+        #[test]
+        fn from_reader_reads_in_pieces() {
+            let mut reader = Trickle { bytes: SMALL_DB, chunk: 7, interrupt: false };
+            assert_eq!(
+                SQLite3Header::from_reader(&mut reader),
+                SQLite3Header::from_bytes(SMALL_DB),
+            );
+            // Nothing past the header is read.
+            assert_eq!(reader.bytes.len(), SMALL_DB.len() - HEADER_SIZE);
+
+            let reader = Trickle { bytes: &SMALL_DB[..60], chunk: 7, interrupt: false };
+            assert_eq!(
+                SQLite3Header::from_reader(reader),
+                Err(Error::UnexpectedEof { expected: 100, found: 60 }),
+            );
+        }
+
+        #[test]
+        fn from_file() {
+            assert_eq!(
+                SQLite3Header::from_file(SMALL_DB_PATH),
+                SQLite3Header::from_bytes(SMALL_DB),
+            );
+            assert_eq!(
+                SQLite3Header::from_file("tests/fixtures/missing.db"),
+                Err(Error::Io(std::io::ErrorKind::NotFound.into())),
+            );
+        }
+
+        #[test]
+        fn try_from_file() {
+            let mut file = tempfile::tempfile().unwrap();
+            file.write_all(SMALL_DB).unwrap();
+            file.seek(SeekFrom::Start(0)).unwrap();
+            assert_eq!(SQLite3Header::try_from(file), SQLite3Header::from_bytes(SMALL_DB));
+
+            let file = File::open(SMALL_DB_PATH).unwrap();
+            let mut reader = file.try_clone().unwrap();
+            SQLite3Header::try_from(file).unwrap();
+            assert_eq!(reader.stream_position().unwrap(), 100);
+        }
+
+        #[test]
+        fn patch_file_writes_only_changed_fields() {
+            // A write version above 2 and a vacuum flag other than 1 are both
+            // normalized by to_bytes, so rewriting the whole header would change them.
+            let mut db = SMALL_DB.to_vec();
+            db[offsets::FILE_FORMAT_WRITE_VERSION] = 5;
+            put_u32(&mut db, offsets::LARGEST_ROOT_BTREE_PAGE, 4);
+            put_u32(&mut db, offsets::INCREMENTAL_VACUUM_MODE, 7);
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(&db).unwrap();
+
+            SQLite3Header::patch_file(file.path(), |header| {
+                header.set_application_id(0x4d50_4258);
+            })
+            .unwrap();
+            let patched = std::fs::read(file.path()).unwrap();
+            assert_eq!(patched.len(), db.len());
+            assert_eq!(&patched[68..72], &[0x4d, 0x50, 0x42, 0x58]);
+            assert_eq!(&patched[..68], &db[..68]);
+            assert_eq!(&patched[72..], &db[72..]);
+
+            SQLite3Header::patch_file(file.path(), |_| {}).unwrap();
+            assert_eq!(std::fs::read(file.path()).unwrap(), patched);
+        }
+
+        #[test]
+        fn patch_file_rejects_invalid_headers() {
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(&SMALL_DB[..HEADER_SIZE - 1]).unwrap();
+            assert!(matches!(
+                SQLite3Header::patch_file(file.path(), |_| unreachable!()),
+                Err(Error::Io(_))
+            ));
+        }
+
+        #[test]
+        fn write_to() {
+            let mut out = Vec::new();
+            SQLite3Header::from_bytes(SMALL_DB).unwrap().write_to(&mut out).unwrap();
+            assert_eq!(&out[..], &SMALL_DB[..HEADER_SIZE]);
+        }
+
+        #[test]
+        fn read_page() {
+            let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+            let mut reader = Cursor::new(SMALL_DB);
+            assert_eq!(header.read_page(&mut reader, 1).unwrap(), &SMALL_DB[..512]);
+            assert_eq!(header.read_page(&mut reader, 23).unwrap(), &SMALL_DB[22 * 512..]);
+            assert_eq!(header.read_page(&mut reader, 0), Err(Error::PageOutOfBounds(0)));
+            assert_eq!(header.read_page(&mut reader, 24), Err(Error::PageOutOfBounds(24)));
+        }
+
+        #[test]
+        fn pages() {
+            let header = SQLite3Header::from_bytes(SMALL_DB).unwrap();
+            let mut reader = Cursor::new(SMALL_DB);
+            let pages = header
+                .pages(&mut reader, SMALL_DB.len() as u64)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(pages.len(), 23);
+            assert_eq!(pages[2], (3, SMALL_DB[1024..1536].to_vec()));
+        }
+
+        /// A file of `len` zero bytes, which is never held in memory.
+        struct Zeros {
+            len: u64,
+            position: u64,
+        }
+
+        impl Read for Zeros {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.len.saturating_sub(self.position).min(buf.len() as u64) as usize;
+                buf[..n].fill(0);
+                self.position += n as u64;
+                Ok(n)
+            }
+        }
+
+        impl Seek for Zeros {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.position = match pos {
+                    SeekFrom::Start(offset) => offset,
+                    SeekFrom::End(offset) => (self.len as i64 + offset) as u64,
+                    SeekFrom::Current(offset) => (self.position as i64 + offset) as u64,
+                };
+                Ok(self.position)
+            }
+        }
+
+        #[test]
+        fn pages_skip_the_lock_byte_page() {
+            let mut bytes = header_bytes();
+            bytes[16..18].copy_from_slice(&[0, 1]);
+            let header = SQLite3Header::from_bytes(&bytes).unwrap();
+            let len = 16_386 * 65_536;
+            let mut reader = Zeros { len, position: 0 };
+            let page_numbers = header
+                .pages(&mut reader, len)
+                .map(|page| page.map(|(page_no, _)| page_no))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(page_numbers.len(), 16_385);
+            assert_eq!(page_numbers[16_383..], [16_384, 16_386]);
+            assert_eq!(header.lock_byte_page_in_file(len), Some(16_385));
+        }
+
+        #[cfg(feature = "tokio")]
+        #[test]
+        fn from_async_reader() {
+            let mut reader = tokio_test::io::Builder::new()
+                .read(&SMALL_DB[..10])
+                .read(&SMALL_DB[10..64])
+                .read(&SMALL_DB[64..100])
+                .build();
+            let header = tokio_test::block_on(SQLite3Header::from_async_reader(&mut reader));
+            assert_eq!(header, SQLite3Header::from_bytes(SMALL_DB));
+
+            let mut reader = tokio_test::io::Builder::new().read(&SMALL_DB[..64]).build();
+            let header = tokio_test::block_on(SQLite3Header::from_async_reader(&mut reader));
+            assert_eq!(header, Err(Error::UnexpectedEof { expected: 100, found: 64 }));
+        }
+
+        #[cfg(feature = "mmap")]
+        #[test]
+        fn from_mmap() {
+            let (header, mmap) = SQLite3Header::from_mmap(SMALL_DB_PATH).unwrap();
+            assert_eq!(Ok(header), SQLite3Header::from_bytes(SMALL_DB));
+            assert_eq!(&mmap[..], SMALL_DB);
+        }
+
+        #[cfg(feature = "bytes")]
+        #[test]
+        fn from_bytes_buf() {
+            let buf = bytes::Bytes::from_static(SMALL_DB);
+            assert_eq!(SQLite3Header::from_bytes_buf(&buf), SQLite3Header::from_bytes(SMALL_DB));
+            assert_eq!(
+                SQLite3HeaderRef::from_bytes_buf(&buf).map(|header| header.page_size_bytes()),
+                Ok(512),
+            );
+        }
+    }
 }
@@ -1,104 +1,184 @@
 use std::{
-    convert::TryInto,
-    array::TryFromSliceError,
+    fs::File,
+    io::{self, Read, Write},
+    process,
 };
 
-const MAGIC_HEADER_STRING: [u8; 16] = [
-    0x53, 0x51, 0x4c, 0x69, 0x74, 0x65, 0x20, 0x66, 0x6f, 0x72, 0x6d, 0x61, 0x74, 0x20, 0x33, 0x00
-];
+use sqlite3_header::{error::Error, offsets::HEADER_LEN, SQLite3Header, SQLite3HeaderRef};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let contents = std::fs::read("data.sqlite")?;
+const USAGE: &str = "usage: sqlite3-header [--json | --field <name> | --raw] [--file] <database>
+  --json requires a build with the json feature (cargo install --features json)";
 
-    assert!(contents[0..16] == MAGIC_HEADER_STRING);
-    println!("MAGIC HEADER STRING: {}", String::from_utf8_lossy(&contents[0..16]));
-
-    let bytes: [u8; 2] = contents[16..18].try_into().unwrap();
-    let page_size = u16::from_be_bytes(bytes);
-
-    assert!(page_size.is_power_of_two());
-    assert!(page_size > 511);
-    assert!(page_size < 32769);
-
-    println!("PAGE SIZE: {}", page_size);
-
-    println!("FILE FORMAT WRITE VERSION: {}", match contents[18] {
-        1 => "legacy",
-        2 => "Write-Ahead Logging",
-        _ => unimplemented!(),
-    });
-    println!("FILE FORMAT READ VERSION: {}", match contents[19] {
-        1 => "legacy",
-        2 => "Write-Ahead Logging",
-        _ => unimplemented!(),
-    });
-
-    println!("RESERVED BYTES PER PAGE: {}", contents[20]);
-
-    assert!(contents[21] == 64);
-    println!("MAXIMUM EMBEDDED PAYLOAD FRACTION: {}", contents[21]);
-
-    assert!(contents[22] == 32);
-    println!("MINIMUM EMBEDDED PAYLOAD FRACTION: {}", contents[22]);
-
-    assert!(contents[23] == 32);
-    println!("LEAF PAYLOAD FRACTION: {}", contents[23]);
-
-    let file_change_counter = slice_to_word(&contents[24..28])?;
-    println!("FILE CHANGE COUNTER: {}", file_change_counter);
-
-    let in_header_database_size = slice_to_word(&contents[28..32])?;
-    println!("IN-HEADER DATABASE SIZE: {:?}", in_header_database_size);
-
-    let freelist_index = slice_to_word(&contents[32..36])?;
-    println!("FREELIST PAGE INDEX: {}", freelist_index);
-
-    let freelist_count = slice_to_word(&contents[36..40])?;
-    println!("FREELIST COUNT: {}", freelist_count);
-
-    let schema_cookie = slice_to_word(&contents[40..44])?;
-    println!("SCHEMA COOKIE: {}", schema_cookie);
-
-    let schema_format = slice_to_word(&contents[44..48])?;
-    assert!([1, 2, 3, 4].contains(&schema_format));
-    println!("SCHEMA FORMAT: {}", schema_format);
-
-    let default_page_cache_size = slice_to_word(&contents[48..52])?;
-    println!("DEFAULT PAGE CACHE SIZE: {}", default_page_cache_size);
-
-    let btree_largest_page_root = slice_to_word(&contents[52..56])?;
-    println!("LARGEST ROOT B-TREE PAGE: {}", btree_largest_page_root);
+/// What to print about the header.
+enum Mode {
+    Report,
+    Json,
+    Field(String),
+    Raw,
+}
 
-    let db_encoding = slice_to_word(&contents[56..60])?;
-    println!("DATABASE TEXT ENCODING: {}", match db_encoding {
-        1 => "UTF-8",
-        2 => "UTF-16le",
-        3 => "UTF-16be",
-        _ => unimplemented!(),
-    });
+struct Options {
+    path: String,
+    mode: Mode,
+}
 
-    let user_version = slice_to_word(&contents[60..64])?;
-    println!("USER VERSION: {}", user_version);
+fn parse_args() -> Result<Options, String> {
+    let mut path = None;
+    let mut mode = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let selected = match arg.as_str() {
+            "--json" => Mode::Json,
+            "--raw" => Mode::Raw,
+            "--field" => match args.next() {
+                Some(value) => Mode::Field(value),
+                None => return Err("--field requires a field name".to_owned()),
+            },
+            "--file" => {
+                match args.next() {
+                    Some(value) => path = Some(value),
+                    None => return Err("--file requires a path".to_owned()),
+                }
+                continue;
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ if path.is_some() => return Err(format!("unexpected argument {}", arg)),
+            _ => {
+                path = Some(arg);
+                continue;
+            }
+        };
+        if mode.replace(selected).is_some() {
+            return Err("only one of --json, --field, and --raw may be given".to_owned());
+        }
+    }
+
+    match path {
+        Some(path) => Ok(Options {
+            path,
+            mode: mode.unwrap_or(Mode::Report),
+        }),
+        None => Err("missing database path".to_owned()),
+    }
+}
 
-    let incremental_vacuum_mode = slice_to_word(&contents[64..68])? != 0;
-    println!("INCREMENTAL-VACUUM MODE: {}", incremental_vacuum_mode);
+/// Describes a parse error for the user, explaining a file too short to hold a header
+/// rather than reporting the byte counts of the failed read.
+fn error_message(e: &Error) -> String {
+    match e {
+        Error::TooShort { found, .. } | Error::UnexpectedEof { found, .. } => {
+            format!("file is only {} bytes; not a valid SQLite header", found)
+        }
+        _ => e.to_string(),
+    }
+}
 
-    let application_id = slice_to_word(&contents[68..72])?;
-    println!("APPLICATION ID: {}", application_id);
+/// Normalizes a field name so that `page_size`, `page-size`, and the report name
+/// `PAGE SIZE` all match.
+fn normalize_field(name: &str) -> String {
+    name.to_ascii_lowercase().replace([' ', '-'], "_")
+}
 
-    let reserved = &contents[72..92];
-    assert!(reserved.iter().all(|&v| v == 0));
+/// Prints the decoded value of the header field `name`, one of the names listed by
+/// `SQLite3Header::report_rows`.
+fn print_field(header: &SQLite3Header, name: &str) -> i32 {
+    let normalized = normalize_field(name);
+    match header
+        .report_rows()
+        .into_iter()
+        .find(|(field, _)| normalize_field(field) == normalized)
+    {
+        Some((_, value)) => {
+            println!("{}", value);
+            0
+        }
+        None => {
+            eprintln!("unknown field {}", name);
+            2
+        }
+    }
+}
 
-    let version_valid_for_number = slice_to_word(&contents[92..96])?;
-    println!("VERSION VALID FOR NUMBER: {}", version_valid_for_number);
+/// Writes the first 100 bytes of the file at `path` to stdout verbatim, once they are
+/// known to begin with the magic header string. Errors go to stderr only.
+fn print_raw(path: &str) -> i32 {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    let read = File::open(path)
+        .and_then(|file| file.take(HEADER_LEN as u64).read_to_end(&mut bytes));
+    if let Err(e) = read {
+        eprintln!("{}: {}", path, e);
+        return 1;
+    }
+    let header = match SQLite3HeaderRef::new(&bytes) {
+        Ok(header) => header,
+        Err(e) => {
+            eprintln!("{}: {}", path, error_message(&e));
+            return 1;
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(header.as_bytes()).and_then(|_| stdout.flush()) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
 
-    let sqlite_version_number = slice_to_word(&contents[96..100])?;
-    println!("SQLITE VERSION NUMBER: {}", sqlite_version_number);
+#[cfg(feature = "json")]
+fn print_json(path: &str, result: Result<SQLite3Header, Error>) -> i32 {
+    let output = result
+        .map_err(|e| error_message(&e))
+        .and_then(|header| serde_json::to_string(&header).map_err(|e| e.to_string()));
+
+    match output {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            println!("{}", serde_json::json!({ "path": path, "error": e }));
+            1
+        }
+    }
+}
 
-    Ok(())
+#[cfg(not(feature = "json"))]
+fn print_json(_: &str, _: Result<SQLite3Header, Error>) -> i32 {
+    eprintln!("sqlite3-header was built without the json feature");
+    2
 }
 
-fn slice_to_word(slice: &[u8]) -> Result<u32, TryFromSliceError> {
-    let bytes: [u8; 4] = slice.try_into()?;
-    Ok(u32::from_be_bytes(bytes))
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    match options.mode {
+        Mode::Raw => process::exit(print_raw(&options.path)),
+        Mode::Json => {
+            let result = SQLite3Header::from_file(&options.path);
+            process::exit(print_json(&options.path, result));
+        }
+        Mode::Report | Mode::Field(_) => {}
+    }
+
+    let header = match SQLite3Header::from_file(&options.path) {
+        Ok(header) => header,
+        Err(e) => {
+            eprintln!("{}: {}", options.path, error_message(&e));
+            process::exit(1);
+        }
+    };
+    match &options.mode {
+        Mode::Field(name) => process::exit(print_field(&header, name)),
+        _ => println!("{}", header),
+    }
 }
@@ -0,0 +1,176 @@
+//! Byte offsets and lengths of every field of the 100-byte database header.
+
+/// The header string: "SQLite format 3\000".
+pub const MAGIC_HEADER_STRING: usize = 0;
+pub const MAGIC_HEADER_STRING_LEN: usize = 16;
+
+/// The database page size in bytes.
+pub const PAGE_SIZE: usize = 16;
+pub const PAGE_SIZE_LEN: usize = 2;
+
+/// File format write version. 1 for legacy; 2 for WAL.
+pub const FILE_FORMAT_WRITE_VERSION: usize = 18;
+pub const FILE_FORMAT_WRITE_VERSION_LEN: usize = 1;
+
+/// File format read version. 1 for legacy; 2 for WAL.
+pub const FILE_FORMAT_READ_VERSION: usize = 19;
+pub const FILE_FORMAT_READ_VERSION_LEN: usize = 1;
+
+/// Bytes of unused "reserved" space at the end of each page.
+pub const RESERVED_BYTES_PER_PAGE: usize = 20;
+pub const RESERVED_BYTES_PER_PAGE_LEN: usize = 1;
+
+/// Maximum embedded payload fraction. Must be 64.
+pub const MAXIMUM_EMBEDDED_PAYLOAD_FRACTION: usize = 21;
+pub const MAXIMUM_EMBEDDED_PAYLOAD_FRACTION_LEN: usize = 1;
+
+/// Minimum embedded payload fraction. Must be 32.
+pub const MINIMUM_EMBEDDED_PAYLOAD_FRACTION: usize = 22;
+pub const MINIMUM_EMBEDDED_PAYLOAD_FRACTION_LEN: usize = 1;
+
+/// Leaf payload fraction. Must be 32.
+pub const LEAF_PAYLOAD_FRACTION: usize = 23;
+pub const LEAF_PAYLOAD_FRACTION_LEN: usize = 1;
+
+/// File change counter.
+pub const FILE_CHANGE_COUNTER: usize = 24;
+pub const FILE_CHANGE_COUNTER_LEN: usize = 4;
+
+/// Size of the database file in pages. The "in-header database size".
+pub const IN_HEADER_DATABASE_SIZE: usize = 28;
+pub const IN_HEADER_DATABASE_SIZE_LEN: usize = 4;
+
+/// Page number of the first freelist trunk page.
+pub const FREELIST_PAGE_INDEX: usize = 32;
+pub const FREELIST_PAGE_INDEX_LEN: usize = 4;
+
+/// Total number of freelist pages.
+pub const FREELIST_COUNT: usize = 36;
+pub const FREELIST_COUNT_LEN: usize = 4;
+
+/// The schema cookie.
+pub const SCHEMA_COOKIE: usize = 40;
+pub const SCHEMA_COOKIE_LEN: usize = 4;
+
+/// The schema format number. Supported schema formats are 1, 2, 3, and 4.
+pub const SCHEMA_FORMAT: usize = 44;
+pub const SCHEMA_FORMAT_LEN: usize = 4;
+
+/// Default page cache size.
+pub const DEFAULT_PAGE_CACHE_SIZE: usize = 48;
+pub const DEFAULT_PAGE_CACHE_SIZE_LEN: usize = 4;
+
+/// The page number of the largest root b-tree page when in auto-vacuum or
+/// incremental-vacuum modes, or zero otherwise.
+pub const LARGEST_ROOT_BTREE_PAGE: usize = 52;
+pub const LARGEST_ROOT_BTREE_PAGE_LEN: usize = 4;
+
+/// The database text encoding. A value of 1 means UTF-8. A value of 2 means
+/// UTF-16le. A value of 3 means UTF-16be.
+pub const DATABASE_TEXT_ENCODING: usize = 56;
+pub const DATABASE_TEXT_ENCODING_LEN: usize = 4;
+
+/// The "user version" as read and set by the user_version pragma.
+pub const USER_VERSION: usize = 60;
+pub const USER_VERSION_LEN: usize = 4;
+
+/// True (non-zero) for incremental-vacuum mode. False (zero) otherwise.
+pub const INCREMENTAL_VACUUM_MODE: usize = 64;
+pub const INCREMENTAL_VACUUM_MODE_LEN: usize = 4;
+
+/// The "Application ID" set by PRAGMA application_id.
+pub const APPLICATION_ID: usize = 68;
+pub const APPLICATION_ID_LEN: usize = 4;
+
+/// Reserved for expansion. Must be zero.
+pub const RESERVED: usize = 72;
+pub const RESERVED_LEN: usize = 20;
+
+/// The version-valid-for number.
+pub const VERSION_VALID_FOR: usize = 92;
+pub const VERSION_VALID_FOR_LEN: usize = 4;
+
+/// SQLITE_VERSION_NUMBER
+pub const SQLITE_VERSION_NUMBER: usize = 96;
+pub const SQLITE_VERSION_NUMBER_LEN: usize = 4;
+
+/// The total size of the database header.
+pub const HEADER_LEN: usize = 100;
+
+/// The name, offset, and length of every header field, in header order. Together
+/// the fields cover the whole header without gaps or overlaps.
+pub const FIELDS: [(&str, usize, usize); 23] = [
+    ("magic header string", MAGIC_HEADER_STRING, MAGIC_HEADER_STRING_LEN),
+    ("page size", PAGE_SIZE, PAGE_SIZE_LEN),
+    ("file format write version", FILE_FORMAT_WRITE_VERSION, FILE_FORMAT_WRITE_VERSION_LEN),
+    ("file format read version", FILE_FORMAT_READ_VERSION, FILE_FORMAT_READ_VERSION_LEN),
+    ("reserved bytes per page", RESERVED_BYTES_PER_PAGE, RESERVED_BYTES_PER_PAGE_LEN),
+    (
+        "maximum embedded payload fraction",
+        MAXIMUM_EMBEDDED_PAYLOAD_FRACTION,
+        MAXIMUM_EMBEDDED_PAYLOAD_FRACTION_LEN,
+    ),
+    (
+        "minimum embedded payload fraction",
+        MINIMUM_EMBEDDED_PAYLOAD_FRACTION,
+        MINIMUM_EMBEDDED_PAYLOAD_FRACTION_LEN,
+    ),
+    ("leaf payload fraction", LEAF_PAYLOAD_FRACTION, LEAF_PAYLOAD_FRACTION_LEN),
+    ("file change counter", FILE_CHANGE_COUNTER, FILE_CHANGE_COUNTER_LEN),
+    ("in-header database size", IN_HEADER_DATABASE_SIZE, IN_HEADER_DATABASE_SIZE_LEN),
+    ("freelist page index", FREELIST_PAGE_INDEX, FREELIST_PAGE_INDEX_LEN),
+    ("freelist count", FREELIST_COUNT, FREELIST_COUNT_LEN),
+    ("schema cookie", SCHEMA_COOKIE, SCHEMA_COOKIE_LEN),
+    ("schema format", SCHEMA_FORMAT, SCHEMA_FORMAT_LEN),
+    ("default page cache size", DEFAULT_PAGE_CACHE_SIZE, DEFAULT_PAGE_CACHE_SIZE_LEN),
+    ("largest root b-tree page", LARGEST_ROOT_BTREE_PAGE, LARGEST_ROOT_BTREE_PAGE_LEN),
+    ("database text encoding", DATABASE_TEXT_ENCODING, DATABASE_TEXT_ENCODING_LEN),
+    ("user version", USER_VERSION, USER_VERSION_LEN),
+    ("incremental-vacuum mode", INCREMENTAL_VACUUM_MODE, INCREMENTAL_VACUUM_MODE_LEN),
+    ("application id", APPLICATION_ID, APPLICATION_ID_LEN),
+    ("reserved for expansion", RESERVED, RESERVED_LEN),
+    ("version valid for number", VERSION_VALID_FOR, VERSION_VALID_FOR_LEN),
+    ("sqlite version number", SQLITE_VERSION_NUMBER, SQLITE_VERSION_NUMBER_LEN),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_cover_the_header() {
+        let mut end = 0;
+        for &(_, offset, len) in FIELDS.iter() {
+            assert_eq!(offset, end);
+            end += len;
+        }
+        assert_eq!(end, HEADER_LEN);
+    }
+
+    #[test]
+    fn offsets_match_the_spec() {
+        assert_eq!(MAGIC_HEADER_STRING, 0);
+        assert_eq!(PAGE_SIZE, 16);
+        assert_eq!(FILE_FORMAT_WRITE_VERSION, 18);
+        assert_eq!(FILE_FORMAT_READ_VERSION, 19);
+        assert_eq!(RESERVED_BYTES_PER_PAGE, 20);
+        assert_eq!(MAXIMUM_EMBEDDED_PAYLOAD_FRACTION, 21);
+        assert_eq!(MINIMUM_EMBEDDED_PAYLOAD_FRACTION, 22);
+        assert_eq!(LEAF_PAYLOAD_FRACTION, 23);
+        assert_eq!(FILE_CHANGE_COUNTER, 24);
+        assert_eq!(IN_HEADER_DATABASE_SIZE, 28);
+        assert_eq!(FREELIST_PAGE_INDEX, 32);
+        assert_eq!(FREELIST_COUNT, 36);
+        assert_eq!(SCHEMA_COOKIE, 40);
+        assert_eq!(SCHEMA_FORMAT, 44);
+        assert_eq!(DEFAULT_PAGE_CACHE_SIZE, 48);
+        assert_eq!(LARGEST_ROOT_BTREE_PAGE, 52);
+        assert_eq!(DATABASE_TEXT_ENCODING, 56);
+        assert_eq!(USER_VERSION, 60);
+        assert_eq!(INCREMENTAL_VACUUM_MODE, 64);
+        assert_eq!(APPLICATION_ID, 68);
+        assert_eq!(RESERVED, 72);
+        assert_eq!(VERSION_VALID_FOR, 92);
+        assert_eq!(SQLITE_VERSION_NUMBER, 96);
+    }
+}
@@ -0,0 +1,296 @@
+// https://sqlite.org/fileformat2.html#record_format
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{error::Error, varint::read_varint, DatabaseTextEncoding};
+
+/// The serial type of a column in a record, which determines how its value is
+/// stored in the record body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SerialType {
+    /// Value is a NULL.
+    Null,
+    /// Value is an 8-bit twos-complement integer.
+    Int8,
+    /// Value is a big-endian 16-bit twos-complement integer.
+    Int16,
+    /// Value is a big-endian 24-bit twos-complement integer.
+    Int24,
+    /// Value is a big-endian 32-bit twos-complement integer.
+    Int32,
+    /// Value is a big-endian 48-bit twos-complement integer.
+    Int48,
+    /// Value is a big-endian 64-bit twos-complement integer.
+    Int64,
+    /// Value is a big-endian IEEE 754-2008 64-bit floating point number.
+    Float64,
+    /// Value is the integer 0. (Only available for schema format 4 and higher.)
+    Zero,
+    /// Value is the integer 1. (Only available for schema format 4 and higher.)
+    One,
+    /// Value is a BLOB that is the given number of bytes in length.
+    Blob(usize),
+    /// Value is a string in the text encoding and the given number of bytes in
+    /// length. The nul terminator is not stored.
+    Text(usize),
+}
+
+impl SerialType {
+    /// Decodes a serial type code. Codes 10 and 11 are reserved for internal use and
+    /// are rejected.
+    pub fn from_code(code: u64) -> Result<Self, Error> {
+        Ok(match code {
+            0 => Self::Null,
+            1 => Self::Int8,
+            2 => Self::Int16,
+            3 => Self::Int24,
+            4 => Self::Int32,
+            5 => Self::Int48,
+            6 => Self::Int64,
+            7 => Self::Float64,
+            8 => Self::Zero,
+            9 => Self::One,
+            10 | 11 => return Err(Error::InvalidSerialType(code)),
+            n if n % 2 == 0 => Self::Blob(((n - 12) / 2) as usize),
+            n => Self::Text(((n - 13) / 2) as usize),
+        })
+    }
+
+    /// The number of bytes the value occupies in the record body.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Null | Self::Zero | Self::One => 0,
+            Self::Int8 => 1,
+            Self::Int16 => 2,
+            Self::Int24 => 3,
+            Self::Int32 => 4,
+            Self::Int48 => 6,
+            Self::Int64 | Self::Float64 => 8,
+            Self::Blob(n) | Self::Text(n) => *n,
+        }
+    }
+}
+
+/// A record header begins with a single varint which determines the total number
+/// of bytes in the header. The varint value is the size of the header in bytes
+/// including the size varint itself. Following the size varint are one or more
+/// additional varints, one per column. These additional varints are called "serial
+/// type" numbers and determine the datatype of each column.
+#[derive(Debug)]
+pub struct RecordHeader {
+    /// The size of the header in bytes, which is the offset of the record body.
+    pub size: usize,
+    pub serial_types: Vec<SerialType>,
+}
+
+impl RecordHeader {
+    /// Parses the header at the start of the record `bytes`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let (size, mut offset) = read_varint(bytes)?;
+        let size = size as usize;
+        if bytes.len() < size {
+            return Err(Error::TooShort {
+                expected: size,
+                found: bytes.len(),
+            });
+        }
+
+        let mut serial_types = Vec::new();
+        while offset < size {
+            let (code, consumed) = read_varint(&bytes[offset..size])?;
+            serial_types.push(SerialType::from_code(code)?);
+            offset += consumed;
+        }
+        Ok(Self { size, serial_types })
+    }
+}
+
+/// A column value decoded from a record.
+#[derive(Debug)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// Reads a value of type `ty` from the start of `data`, decoding text in the
+/// database text `encoding`.
+pub fn read_value(
+    ty: &SerialType,
+    data: &[u8],
+    encoding: DatabaseTextEncoding,
+) -> Result<Value, Error> {
+    let size = ty.size();
+    if data.len() < size {
+        return Err(Error::TooShort {
+            expected: size,
+            found: data.len(),
+        });
+    }
+    let data = &data[..size];
+
+    Ok(match ty {
+        SerialType::Null => Value::Null,
+        SerialType::Zero => Value::Int(0),
+        SerialType::One => Value::Int(1),
+        SerialType::Int8
+        | SerialType::Int16
+        | SerialType::Int24
+        | SerialType::Int32
+        | SerialType::Int48
+        | SerialType::Int64 => {
+            // Sign-extend from the most significant byte.
+            let init = if data[0] & 0x80 != 0 { -1 } else { 0 };
+            Value::Int(data.iter().fold(init, |v, &b| (v << 8) | i64::from(b)))
+        }
+        SerialType::Float64 => {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(data);
+            Value::Float(f64::from_bits(u64::from_be_bytes(bytes)))
+        }
+        SerialType::Blob(_) => Value::Blob(data.to_vec()),
+        SerialType::Text(_) => Value::Text(decode_text(data, encoding)?),
+    })
+}
+
+/// Decodes text stored in the database text `encoding`.
+fn decode_text(data: &[u8], encoding: DatabaseTextEncoding) -> Result<String, Error> {
+    let from_bytes: fn([u8; 2]) -> u16 = match encoding {
+        DatabaseTextEncoding::Utf8 => {
+            return String::from_utf8(data.to_vec()).map_err(|_| Error::InvalidTextEncodingContent)
+        }
+        DatabaseTextEncoding::Utf16le => u16::from_le_bytes,
+        DatabaseTextEncoding::Utf16be => u16::from_be_bytes,
+    };
+    let pairs = data.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(Error::InvalidTextEncodingContent);
+    }
+    let units = pairs.map(|pair| from_bytes([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|_| Error::InvalidTextEncodingContent)
+}
+
+/// Decodes every column value of the record `payload`, which must hold the whole
+/// record including any part of it stored on overflow pages. Text is decoded in the
+/// database text `encoding`.
+pub fn decode_record(
+    payload: &[u8],
+    encoding: DatabaseTextEncoding,
+) -> Result<Vec<Value>, Error> {
+    let header = RecordHeader::parse(payload)?;
+    let mut offset = header.size;
+    let mut values = Vec::with_capacity(header.serial_types.len());
+    for ty in &header.serial_types {
+        let data = payload.get(offset..).ok_or(Error::TooShort {
+            expected: offset,
+            found: payload.len(),
+        })?;
+        values.push(read_value(ty, data, encoding)?);
+        offset += ty.size();
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn serial_types() {
+        assert_eq!(SerialType::from_code(0), Ok(SerialType::Null));
+        assert_eq!(SerialType::from_code(7), Ok(SerialType::Float64));
+        assert_eq!(SerialType::from_code(9), Ok(SerialType::One));
+        assert_eq!(SerialType::from_code(10), Err(Error::InvalidSerialType(10)));
+        assert_eq!(SerialType::from_code(11), Err(Error::InvalidSerialType(11)));
+        assert_eq!(SerialType::from_code(12), Ok(SerialType::Blob(0)));
+        assert_eq!(SerialType::from_code(13), Ok(SerialType::Text(0)));
+        assert_eq!(SerialType::from_code(18), Ok(SerialType::Blob(3)));
+        assert_eq!(SerialType::from_code(19), Ok(SerialType::Text(3)));
+
+        assert_eq!(SerialType::Int48.size(), 6);
+        assert_eq!(SerialType::One.size(), 0);
+        assert_eq!(SerialType::Text(3).size(), 3);
+    }
+
+    #[test]
+    fn parses_record_header() {
+        let header = RecordHeader::parse(&[4, 0, 1, 23, 0xff]).unwrap();
+        assert_eq!(header.size, 4);
+        assert_eq!(
+            header.serial_types,
+            vec![SerialType::Null, SerialType::Int8, SerialType::Text(5)],
+        );
+        assert_eq!(
+            RecordHeader::parse(&[4, 0]).unwrap_err(),
+            Error::TooShort { expected: 4, found: 2 },
+        );
+    }
+
+    #[test]
+    fn reads_integers_sign_extended() {
+        let encoding = DatabaseTextEncoding::Utf8;
+        assert!(matches!(read_value(&SerialType::Int8, &[0xff], encoding), Ok(Value::Int(-1))));
+        assert!(matches!(
+            read_value(&SerialType::Int24, &[0x80, 0, 0], encoding),
+            Ok(Value::Int(-8_388_608))
+        ));
+        assert!(matches!(
+            read_value(&SerialType::Int48, &[0, 0, 0, 1, 0, 0], encoding),
+            Ok(Value::Int(65536))
+        ));
+        assert!(matches!(read_value(&SerialType::Zero, &[], encoding), Ok(Value::Int(0))));
+        assert!(matches!(
+            read_value(&SerialType::Float64, &1.5f64.to_be_bytes(), encoding),
+            Ok(Value::Float(f)) if f == 1.5
+        ));
+        assert_eq!(
+            read_value(&SerialType::Int32, &[1, 2], encoding).unwrap_err(),
+            Error::TooShort { expected: 4, found: 2 },
+        );
+    }
+
+    #[test]
+    fn decodes_record() {
+        let record = [5, 0, 1, 19, 14, 42, b'a', b'b', b'c', 0xde];
+        let values = decode_record(&record, DatabaseTextEncoding::Utf8).unwrap();
+        assert!(matches!(
+            &values[..],
+            [Value::Null, Value::Int(42), Value::Text(text), Value::Blob(blob)]
+                if text == "abc" && blob == &[0xde]
+        ));
+        assert_eq!(
+            decode_record(&record[..9], DatabaseTextEncoding::Utf8).unwrap_err(),
+            Error::TooShort { expected: 1, found: 0 },
+        );
+    }
+
+    #[test]
+    fn decodes_text_in_database_encoding() {
+        let text = |data: &[u8], encoding| match read_value(
+            &SerialType::Text(data.len()),
+            data,
+            encoding,
+        ) {
+            Ok(Value::Text(text)) => Ok(text),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(e),
+        };
+        assert_eq!(text(b"h\xc3\xa9", DatabaseTextEncoding::Utf8), Ok("hé".into()));
+        assert_eq!(text(&[b'h', 0, 0xe9, 0], DatabaseTextEncoding::Utf16le), Ok("hé".into()));
+        assert_eq!(text(&[0, b'h', 0, 0xe9], DatabaseTextEncoding::Utf16be), Ok("hé".into()));
+
+        let invalid = Err(Error::InvalidTextEncodingContent);
+        assert_eq!(text(&[0xff], DatabaseTextEncoding::Utf8), invalid);
+        assert_eq!(text(b"h", DatabaseTextEncoding::Utf16le), invalid);
+        assert_eq!(
+            text(&[0, 0xd8, b'h', 0], DatabaseTextEncoding::Utf16le),
+            Err(Error::InvalidTextEncodingContent),
+        );
+    }
+}
@@ -0,0 +1,297 @@
+// https://sqlite.org/fileformat2.html#storage_of_the_sql_database_schema
+
+use alloc::{string::String, vec, vec::Vec};
+use core::convert::TryFrom;
+
+use crate::{
+    btree::{
+        max_local_payload, parse_table_leaf_cell, BTreePageHeader, CellPointers, OverflowChain,
+        PageType,
+    },
+    error::Error,
+    four_byte_slice_to_u32, offsets, page,
+    record::{decode_record, Value},
+    text_encoding_from_u32, u32_at, DatabaseTextEncoding, HEADER_SIZE,
+};
+
+/// Page 1 of a database file is the root page of a table b-tree that holds a
+/// special table named "sqlite_schema". This b-tree is known as the "schema table"
+/// since it stores the complete database schema. Each row of the schema table
+/// describes one table, index, view, or trigger.
+#[derive(Debug)]
+pub struct SchemaEntry {
+    /// One of the text strings 'table', 'index', 'view', or 'trigger'.
+    pub entry_type: String,
+    /// The name of the object.
+    pub name: String,
+    /// The name of the table or view that the object is associated with. For a table
+    /// or view, this is a copy of `name`.
+    pub tbl_name: String,
+    /// The page number of the root b-tree page for tables and indexes. For views,
+    /// triggers, and virtual tables this is 0.
+    pub rootpage: u32,
+    /// The SQL text that describes the object, or `None` for the internal indexes
+    /// created by UNIQUE or PRIMARY KEY constraints.
+    pub sql: Option<String>,
+}
+
+/// Reads every entry of the schema table from the bytes of the whole database `db`.
+pub fn read_schema(db: &[u8], page_size: u32) -> Result<Vec<SchemaEntry>, Error> {
+    SchemaCursor::new(db, page_size)?.collect()
+}
+
+/// An iterator over the entries of the schema table that reads one entry at a time,
+/// descending through the interior pages of the schema b-tree as needed. After an
+/// error, no more entries are yielded.
+#[derive(Debug)]
+pub struct SchemaCursor<'a> {
+    db: &'a [u8],
+    page_size: u32,
+    usable_size: u32,
+    encoding: DatabaseTextEncoding,
+    /// The pages yet to be visited, with the next one at the end.
+    pending: Vec<u32>,
+    /// The usable region of the leaf page being read and its remaining cells.
+    leaf: Option<(&'a [u8], CellPointers<'a>)>,
+    /// No b-tree can visit more pages than the database holds, so this many more
+    /// pages may be visited before the b-tree is known to contain a cycle.
+    remaining: usize,
+}
+
+impl<'a> SchemaCursor<'a> {
+    /// Starts reading the schema table from the bytes of the whole database `db`.
+    pub fn new(db: &'a [u8], page_size: u32) -> Result<Self, Error> {
+        if db.len() < HEADER_SIZE {
+            return Err(Error::TooShort {
+                expected: HEADER_SIZE,
+                found: db.len(),
+            });
+        }
+        let usable_size = page_size
+            .checked_sub(u32::from(db[offsets::RESERVED_BYTES_PER_PAGE]))
+            .ok_or(Error::InvalidReservedSpace {
+                page_size,
+                reserved: db[offsets::RESERVED_BYTES_PER_PAGE],
+            })?;
+        let encoding = text_encoding_from_u32(u32_at(db, offsets::DATABASE_TEXT_ENCODING)?)?;
+
+        Ok(Self {
+            db,
+            page_size,
+            usable_size,
+            encoding,
+            pending: vec![1],
+            leaf: None,
+            remaining: db.len() / page_size.max(1) as usize,
+        })
+    }
+
+    fn next_entry(&mut self) -> Result<Option<SchemaEntry>, Error> {
+        loop {
+            if let Some((bytes, cells)) = &mut self.leaf {
+                if let Some(pointer) = cells.next() {
+                    let cell = &bytes[usize::from(pointer?)..];
+                    return read_entry(
+                        self.db,
+                        self.page_size,
+                        self.usable_size,
+                        self.encoding,
+                        cell,
+                    )
+                    .map(Some);
+                }
+                self.leaf = None;
+            }
+
+            let page_number = match self.pending.pop() {
+                Some(page_number) => page_number,
+                None => return Ok(None),
+            };
+            if self.remaining == 0 {
+                return Err(Error::PageCycle(page_number));
+            }
+            self.remaining -= 1;
+
+            let bytes = page(self.db, page_number, self.page_size)?;
+            let bytes = &bytes[..self.usable_size as usize];
+            let offset = if page_number == 1 { HEADER_SIZE } else { 0 };
+            let header = BTreePageHeader::parse(bytes.get(offset..).ok_or(Error::TooShort {
+                expected: offset,
+                found: bytes.len(),
+            })?)?;
+
+            match header.page_type {
+                PageType::InteriorTable => {
+                    let mut children = Vec::new();
+                    for pointer in header.cell_pointers(bytes, offset) {
+                        let cell = &bytes[usize::from(pointer?)..];
+                        let child = cell.get(0..4).ok_or(Error::InvalidCell(page_number))?;
+                        children.push(four_byte_slice_to_u32(child)?);
+                    }
+                    children.extend(header.right_most_pointer);
+                    self.pending.extend(children.into_iter().rev());
+                }
+                PageType::LeafTable => {
+                    self.leaf = Some((bytes, header.cell_pointers(bytes, offset)));
+                }
+                _ => return Err(Error::InvalidPageType(bytes[offset])),
+            }
+        }
+    }
+}
+
+impl Iterator for SchemaCursor<'_> {
+    type Item = Result<SchemaEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.next_entry().transpose();
+        if let Some(Err(_)) = entry {
+            self.pending.clear();
+            self.leaf = None;
+        }
+        entry
+    }
+}
+
+/// Reads a schema entry from a table b-tree leaf cell, following its overflow chain
+/// if the record does not fit on the page.
+fn read_entry(
+    db: &[u8],
+    page_size: u32,
+    usable_size: u32,
+    encoding: DatabaseTextEncoding,
+    cell: &[u8],
+) -> Result<SchemaEntry, Error> {
+    let max_local = max_local_payload(&PageType::LeafTable, usable_size);
+    let cell = parse_table_leaf_cell(cell, usable_size, max_local)?;
+    let payload_size = cell.payload_size;
+
+    let mut payload = cell.local_payload.to_vec();
+    if let Some(first) = cell.overflow_page {
+        let chain = OverflowChain::new(first, db, page_size, usable_size);
+        for content in chain {
+            let needed = payload_size as usize - payload.len();
+            let content = content?;
+            payload.extend_from_slice(&content[..needed.min(content.len())]);
+            if payload.len() as u64 == payload_size {
+                break;
+            }
+        }
+        if (payload.len() as u64) < payload_size {
+            return Err(Error::InvalidSchemaEntry);
+        }
+    }
+
+    let mut values = decode_record(&payload, encoding)?.into_iter();
+    let entry_type = text(values.next())?.ok_or(Error::InvalidSchemaEntry)?;
+    let name = text(values.next())?.ok_or(Error::InvalidSchemaEntry)?;
+    let tbl_name = text(values.next())?.ok_or(Error::InvalidSchemaEntry)?;
+    let rootpage = match values.next() {
+        Some(Value::Int(n)) => u32::try_from(n).map_err(|_| Error::InvalidSchemaEntry)?,
+        Some(Value::Null) => 0,
+        _ => return Err(Error::InvalidSchemaEntry),
+    };
+    let sql = text(values.next())?;
+    Ok(SchemaEntry {
+        entry_type,
+        name,
+        tbl_name,
+        rootpage,
+        sql,
+    })
+}
+
+fn text(value: Option<Value>) -> Result<Option<String>, Error> {
+    match value {
+        Some(Value::Text(s)) => Ok(Some(s)),
+        Some(Value::Null) => Ok(None),
+        _ => Err(Error::InvalidSchemaEntry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SQLite3Header;
+
+    /// A database whose schema b-tree has an interior root page and an entry that
+    /// overflows onto another page.
+    const SMALL_DB: &[u8] = include_bytes!("../tests/fixtures/small.db");
+
+    /// A UTF-16le database holding a single table.
+    const UTF16_DB: &[u8] = include_bytes!("../tests/fixtures/utf16.db");
+
+    #[test]
+    fn reads_every_entry() {
+        let entries = read_schema(SMALL_DB, 512).unwrap();
+        let names = entries
+            .iter()
+            .map(|entry| (entry.entry_type.as_str(), entry.name.as_str(), entry.rootpage))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("table", "fruit", 2),
+                ("index", "sqlite_autoindex_fruit_1", 3),
+                ("view", "red", 0),
+                ("table", "wide", 4),
+                ("table", "scratch", 8),
+            ],
+        );
+
+        assert_eq!(entries[1].tbl_name, "fruit");
+        assert_eq!(entries[1].sql, None);
+        assert_eq!(entries[4].sql.as_deref(), Some("CREATE TABLE scratch(x)"));
+    }
+
+    #[test]
+    fn follows_overflow_pages() {
+        let wide = read_schema(SMALL_DB, 512).unwrap().remove(3);
+        let sql = wide.sql.unwrap();
+        assert!(sql.len() > 512);
+        assert!(sql.starts_with("CREATE TABLE wide(column_with_a_rather_long_name_0 INTEGER, "));
+        assert!(sql.ends_with(", column_with_a_rather_long_name_19 INTEGER)"));
+    }
+
+    #[test]
+    fn decodes_utf16_text() {
+        let header = SQLite3Header::from_bytes(UTF16_DB).unwrap();
+        assert_eq!(header.database_text_encoding(), &DatabaseTextEncoding::Utf16le);
+        let entries = read_schema(UTF16_DB, 512).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].sql.as_deref(), Some("CREATE TABLE t(x TEXT)"));
+    }
+
+    #[test]
+    fn cursor_is_lazy() {
+        let mut cursor = SchemaCursor::new(SMALL_DB, 512).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap().name, "fruit");
+        assert_eq!(cursor.count(), 4);
+    }
+
+    #[test]
+    fn rejects_bad_databases() {
+        assert_eq!(
+            SchemaCursor::new(&SMALL_DB[..50], 512).unwrap_err(),
+            Error::TooShort { expected: 100, found: 50 },
+        );
+        assert_eq!(read_schema(&SMALL_DB[..1024], 512).unwrap_err(), Error::PageOutOfBounds(6));
+
+        let mut db = SMALL_DB[..512].to_vec();
+        db[offsets::RESERVED_BYTES_PER_PAGE] = 1;
+        assert_eq!(
+            SchemaCursor::new(&db, 0).unwrap_err(),
+            Error::InvalidReservedSpace { page_size: 0, reserved: 1 },
+        );
+    }
+
+    #[test]
+    fn stops_at_cycles() {
+        // Page 1 is an interior page with no cells whose right-most child is itself.
+        let mut db = SMALL_DB[..512].to_vec();
+        db[100..112].copy_from_slice(&[0x05, 0, 0, 0, 0, 0x01, 0xfc, 0, 0, 0, 0, 1]);
+        let mut cursor = SchemaCursor::new(&db, 512).unwrap();
+        assert_eq!(cursor.next().unwrap().unwrap_err(), Error::PageCycle(1));
+        assert!(cursor.next().is_none());
+    }
+}
@@ -0,0 +1,156 @@
+// https://sqlite.org/fileformat2.html#pointer_map_or_ptrmap_pages
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{error::Error, four_byte_slice_to_u32};
+
+/// Each ptrmap entry consists of a single byte of "page type" information followed
+/// by a 4-byte big-endian page number.
+pub const PTRMAP_ENTRY_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PtrmapType {
+    /// A b-tree root page. The page number should be zero.
+    RootPage,
+    /// A freelist page. The page number should be zero.
+    FreePage,
+    /// The first page of a cell payload overflow chain. The page number is the b-tree
+    /// page that contains the cell whose content has overflowed.
+    Overflow1,
+    /// A page in an overflow chain other than the first page. The page number is the
+    /// prior page of the overflow chain.
+    Overflow2,
+    /// A non-root b-tree page. The page number is the parent b-tree page.
+    BTree,
+}
+
+/// A single entry of a ptrmap page, describing the parent of one database page.
+#[derive(Debug)]
+pub struct PtrmapEntry {
+    pub entry_type: PtrmapType,
+    pub parent_page: u32,
+}
+
+impl PtrmapEntry {
+    /// Parses a single 5-byte ptrmap entry.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < PTRMAP_ENTRY_SIZE {
+            return Err(Error::TooShort {
+                expected: PTRMAP_ENTRY_SIZE,
+                found: bytes.len(),
+            });
+        }
+
+        let entry_type = match bytes[0] {
+            1 => PtrmapType::RootPage,
+            2 => PtrmapType::FreePage,
+            3 => PtrmapType::Overflow1,
+            4 => PtrmapType::Overflow2,
+            5 => PtrmapType::BTree,
+            v => return Err(Error::InvalidPtrmapType(v)),
+        };
+        Ok(Self {
+            entry_type,
+            parent_page: four_byte_slice_to_u32(&bytes[1..5])?,
+        })
+    }
+}
+
+/// Parses the entries of a ptrmap page, which describe the pages immediately
+/// following it in order. Parsing stops at the first all-zero entry, which marks
+/// the end of the database, or after the last complete entry of `page`. Only the
+/// usable size of the page should be passed.
+#[cfg(feature = "alloc")]
+pub fn parse_ptrmap_page(page: &[u8]) -> Result<Vec<PtrmapEntry>, Error> {
+    page.chunks_exact(PTRMAP_ENTRY_SIZE)
+        .take_while(|entry| entry.iter().any(|&b| b != 0))
+        .map(PtrmapEntry::from_bytes)
+        .collect()
+}
+
+/// The page number of the ptrmap page that holds the entry for page `page_no`. The
+/// first ptrmap page is page 2 and each ptrmap page is followed by the
+/// `usable_size / 5` pages it describes, so a ptrmap page is returned for itself
+/// even though it has no entry. Page 1 has no ptrmap entry, so 0 is returned for
+/// it. A ptrmap page that would fall on the lock-byte page is moved to the next page,
+/// which depends on the page size as well as the usable size, so results are only
+/// exact for databases smaller than 1 GiB; use
+/// [`SQLite3Header::ptrmap_page_for`](crate::SQLite3Header::ptrmap_page_for) for
+/// larger ones.
+pub fn ptrmap_page_for(page_no: u32, usable_size: u32) -> u32 {
+    if page_no < 2 {
+        return 0;
+    }
+    let pages_per_map = usable_size / PTRMAP_ENTRY_SIZE as u32 + 1;
+    (page_no - 2) / pages_per_map * pages_per_map + 2
+}
+
+/// Whether page `page_no` is a ptrmap page of an auto-vacuum database, with the
+/// same caveats as [`ptrmap_page_for`]; see
+/// [`SQLite3Header::is_ptrmap_page`](crate::SQLite3Header::is_ptrmap_page).
+pub fn is_ptrmap_page(page_no: u32, usable_size: u32) -> bool {
+    page_no >= 2 && ptrmap_page_for(page_no, usable_size) == page_no
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An incremental-vacuum database with 512-byte pages. Page 2 is its ptrmap page,
+    /// describing pages 3 through 7.
+    #[cfg(feature = "alloc")]
+    const AUTOVACUUM_DB: &[u8] = include_bytes!("../tests/fixtures/autovacuum.db");
+
+    #[test]
+    fn parses_entry() {
+        let entry = PtrmapEntry::from_bytes(&[4, 0, 0, 0, 9]).unwrap();
+        assert_eq!(entry.entry_type, PtrmapType::Overflow2);
+        assert_eq!(entry.parent_page, 9);
+
+        assert_eq!(
+            PtrmapEntry::from_bytes(&[6, 0, 0, 0, 0]).unwrap_err(),
+            Error::InvalidPtrmapType(6)
+        );
+        assert_eq!(
+            PtrmapEntry::from_bytes(&[1, 0]).unwrap_err(),
+            Error::TooShort { expected: 5, found: 2 },
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn parses_fixture_page() {
+        let entries = parse_ptrmap_page(&AUTOVACUUM_DB[512..1024]).unwrap();
+        let entries = entries
+            .iter()
+            .map(|entry| (entry.entry_type, entry.parent_page))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (PtrmapType::RootPage, 0),
+                (PtrmapType::RootPage, 0),
+                (PtrmapType::Overflow1, 3),
+                (PtrmapType::Overflow2, 5),
+                (PtrmapType::Overflow2, 6),
+            ],
+        );
+    }
+
+    #[test]
+    fn ptrmap_page_numbers() {
+        // Each ptrmap page of a 512-byte page describes the 102 pages after it.
+        assert_eq!(ptrmap_page_for(1, 512), 0);
+        assert_eq!(ptrmap_page_for(2, 512), 2);
+        assert_eq!(ptrmap_page_for(3, 512), 2);
+        assert_eq!(ptrmap_page_for(104, 512), 2);
+        assert_eq!(ptrmap_page_for(105, 512), 105);
+        assert_eq!(ptrmap_page_for(106, 512), 105);
+
+        assert!(!is_ptrmap_page(1, 512));
+        assert!(is_ptrmap_page(2, 512));
+        assert!(!is_ptrmap_page(104, 512));
+        assert!(is_ptrmap_page(105, 512));
+    }
+}
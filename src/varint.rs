@@ -0,0 +1,67 @@
+use crate::error::Error;
+
+/// A varint is at most 9 bytes long.
+pub const MAX_VARINT_SIZE: usize = 9;
+
+/// A variable-length integer or "varint" is a static Huffman encoding of 64-bit
+/// twos-complement integers that uses less space for small positive values. A varint
+/// is between 1 and 9 bytes in length. The varint consists of either zero or more
+/// bytes which have the high-order bit set followed by a single byte with the
+/// high-order bit clear, or nine bytes, whichever is shorter. The lower seven bits of
+/// each of the first eight bytes and all 8 bits of the ninth byte are used to
+/// reconstruct the 64-bit twos-complement integer. Varints are big-endian: bits
+/// taken from the earlier byte of the varint are more significant than bits taken
+/// from the later bytes.
+///
+/// Returns the decoded value and the number of bytes it occupied.
+pub fn read_varint(bytes: &[u8]) -> Result<(u64, usize), Error> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(MAX_VARINT_SIZE) {
+        if i == MAX_VARINT_SIZE - 1 {
+            return Ok(((value << 8) | u64::from(byte), MAX_VARINT_SIZE));
+        }
+
+        value = (value << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(Error::TruncatedVarint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_byte() {
+        assert_eq!(read_varint(&[0x00]), Ok((0, 1)));
+        assert_eq!(read_varint(&[0x7f, 0xff]), Ok((0x7f, 1)));
+    }
+
+    #[test]
+    fn multiple_bytes() {
+        for &(bytes, expected) in &[
+            (&[0x81, 0x00][..], (0x80, 2)),
+            (&[0x82, 0x80, 0x01], (0x8001, 3)),
+            (&[0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0x01], (0x0002_0408_1020_4081, 8)),
+        ] {
+            assert_eq!(read_varint(bytes), Ok(expected), "{:02x?}", bytes);
+        }
+    }
+
+    #[test]
+    fn ninth_byte_uses_all_eight_bits() {
+        assert_eq!(read_varint(&[0xff; 9]), Ok((u64::MAX, 9)));
+        let mut bytes = [0x80; 10];
+        bytes[8] = 0xff;
+        bytes[9] = 0;
+        assert_eq!(read_varint(&bytes), Ok((0xff, 9)));
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(read_varint(&[]), Err(Error::TruncatedVarint));
+        assert_eq!(read_varint(&[0x81, 0x81]), Err(Error::TruncatedVarint));
+    }
+}
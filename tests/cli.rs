@@ -0,0 +1,117 @@
+use std::process::{Command, Output};
+
+const SMALL_DB: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/small.db");
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sqlite3-header"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+#[test]
+fn prints_report() {
+    let output = run(&[SMALL_DB]);
+    assert!(output.status.success());
+    let report = stdout(&output);
+    assert!(report.starts_with("MAGIC HEADER STRING: SQLite format 3\n"));
+    assert!(report.contains("\nPAGE SIZE: 512\n"));
+    assert!(report.contains("\nFREELIST COUNT: 15\n"));
+    assert!(report.contains("\nJOURNAL MODE: rollback\n"));
+    assert!(report.ends_with("\nSQLITE VERSION NUMBER: 3040001\nSQLITE VERSION: 3.40.1\n"));
+
+    assert_eq!(stdout(&run(&["--file", SMALL_DB])), report);
+}
+
+#[test]
+fn prints_one_field() {
+    for name in ["page_size", "page-size", "PAGE SIZE"] {
+        let output = run(&["--field", name, SMALL_DB]);
+        assert!(output.status.success());
+        assert_eq!(stdout(&output), "512\n");
+    }
+    assert_eq!(stdout(&run(&["--field", "user_version", SMALL_DB])), "7\n");
+    assert_eq!(stdout(&run(&["--field", "application_id", SMALL_DB])), "0\n");
+
+    let output = run(&["--field", "nonsense", SMALL_DB]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stderr(&output), "unknown field nonsense\n");
+}
+
+#[test]
+fn prints_raw_header() {
+    let output = run(&["--raw", SMALL_DB]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, &std::fs::read(SMALL_DB).unwrap()[..100]);
+}
+
+#[test]
+fn reports_short_files() {
+    let dir = tempfile::tempdir().unwrap();
+    for len in [0, 5, 40] {
+        let path = dir.path().join(format!("short-{}.db", len));
+        std::fs::write(&path, &std::fs::read(SMALL_DB).unwrap()[..len]).unwrap();
+        let path = path.to_str().unwrap();
+
+        for args in [&[path][..], &["--raw", path]] {
+            let output = run(args);
+            assert_eq!(output.status.code(), Some(1));
+            assert!(output.stdout.is_empty());
+            assert_eq!(
+                stderr(&output),
+                format!("{}: file is only {} bytes; not a valid SQLite header\n", path, len)
+            );
+        }
+    }
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    for (args, message) in [
+        (&[][..], "missing database path"),
+        (&["--bogus", SMALL_DB], "unknown option --bogus"),
+        (&["--field"], "--field requires a field name"),
+        (&["--raw", "--field", "page_size", SMALL_DB], "only one of"),
+        (&[SMALL_DB, SMALL_DB], "unexpected argument"),
+    ] {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(output.stdout.is_empty());
+        assert!(stderr(&output).starts_with(message), "{}", stderr(&output));
+        assert!(stderr(&output).contains("usage: sqlite3-header"));
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn prints_json() {
+    let output = run(&["--json", SMALL_DB]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(json["page_size"], 512);
+    assert_eq!(json["freelist"]["count"], 15);
+    assert_eq!(json["last_update"]["sqlite_version_number"], 3040001);
+
+    let output = run(&["--json", "/nonexistent.db"]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(json["path"], "/nonexistent.db");
+    assert!(json["error"].is_string());
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn json_requires_the_json_feature() {
+    let output = run(&["--json", SMALL_DB]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stderr(&output), "sqlite3-header was built without the json feature\n");
+    assert!(stderr(&run(&[])).contains("--json requires a build with the json feature"));
+}